@@ -1,22 +1,44 @@
 use std::{fmt, io};
 use std::fmt::Formatter;
 
+// Result codes reported by the lidar
+pub const SL_RESULT_OK: u32 = 0;
+pub const SL_RESULT_FAIL_BIT: u32 = 0x80000000;
+pub const SL_RESULT_ALREADY_DONE: u32 = 0x20;
+pub const SL_RESULT_INVALID_DATA: u32 = 0x8000 | SL_RESULT_FAIL_BIT;
+pub const SL_RESULT_OPERATION_FAIL: u32 = 0x8001 | SL_RESULT_FAIL_BIT;
+pub const SL_RESULT_OPERATION_TIMEOUT: u32 = 0x8002 | SL_RESULT_FAIL_BIT;
+pub const SL_RESULT_OPERATION_STOP: u32 = 0x8003 | SL_RESULT_FAIL_BIT;
+pub const SL_RESULT_OPERATION_NOT_SUPPORT: u32 = 0x8004 | SL_RESULT_FAIL_BIT;
+pub const SL_RESULT_FORMAT_NOT_SUPPORT: u32 = 0x8005 | SL_RESULT_FAIL_BIT;
+pub const SL_RESULT_INSUFFICIENT_MEMORY: u32 = 0x8006 | SL_RESULT_FAIL_BIT;
+
 #[derive(Debug, Clone)]
 pub enum RxError {
-    Corrupted([u8; 7]),
+    Corrupted(Vec<u8>),
     PortError(serialport::Error),
     TimedOut,
+    OperationNotSupport,
+    ProtectionStop,
+    AlreadyScanning,
+    OperationFail(u32),
 }
 
 impl From<serialport::Error> for RxError {
     fn from(e: serialport::Error) -> Self {
-        RxError::PortError(e)
+        match e.kind {
+            serialport::ErrorKind::Io(io::ErrorKind::TimedOut) => RxError::TimedOut,
+            _ => RxError::PortError(e),
+        }
     }
 }
 
 impl From<io::Error> for RxError {
     fn from(e: io::Error) -> Self {
-        Self::PortError(e.into())
+        match e.kind() {
+            io::ErrorKind::TimedOut => RxError::TimedOut,
+            _ => Self::PortError(e.into()),
+        }
     }
 }
 
@@ -26,6 +48,38 @@ impl fmt::Display for RxError {
             RxError::Corrupted(v) => { write!(f, "CORRUPTED! {:x?}", v) }
             RxError::PortError(err) => { write!(f, "Port error: {}", err) }
             RxError::TimedOut => { write!(f, "Timed out waiting for data") }
+            RxError::OperationNotSupport => { write!(f, "Operation not supported by the lidar") }
+            RxError::ProtectionStop => { write!(f, "Lidar is in protection stop and must be reset") }
+            RxError::AlreadyScanning => { write!(f, "A scan is already running") }
+            RxError::OperationFail(code) => { write!(f, "Operation failed: {:#x}", code) }
         }
     }
+}
+
+impl std::error::Error for RxError {}
+
+/// Maps a result code reported by the lidar to an error
+pub fn result_from_code(code: u32) -> Result<(), RxError> {
+    if code & SL_RESULT_FAIL_BIT == 0 {
+        return Ok(());
+    }
+
+    Err(match code {
+        SL_RESULT_OPERATION_TIMEOUT => RxError::TimedOut,
+        SL_RESULT_OPERATION_NOT_SUPPORT | SL_RESULT_FORMAT_NOT_SUPPORT => RxError::OperationNotSupport,
+        _ => RxError::OperationFail(code),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopped_operation_is_not_a_protection_stop() {
+        assert!(matches!(
+            result_from_code(SL_RESULT_OPERATION_STOP),
+            Err(RxError::OperationFail(SL_RESULT_OPERATION_STOP))
+        ));
+    }
 }
\ No newline at end of file
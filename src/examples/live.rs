@@ -22,15 +22,15 @@ pub fn live_view() -> Result<(), Box<dyn Error>> {
     let info = lidar.get_info();
     let health = lidar.get_health();
 
-    println!("\nModel {} version {}.{} HW {}", info.model, info.firmware_version >> 8, info.firmware_version & 0xff, info.hardware_version);
+    println!("\n{}", info);
 
-    if health.status > 0 {
+    if !health.is_healthy() {
         eprintln!(" code: {}\nexiting!", health.error_code);
         lidar.reset();
         return Ok(());
     }
 
-    /// number of samples
+    // number of samples
     let n: usize = args.points.unwrap_or(5000);
 
     // window dimensions
@@ -45,7 +45,7 @@ pub fn live_view() -> Result<(), Box<dyn Error>> {
     // read/display loop
     run_context(move || {
         println!("Starting scan ({} sample{})...", n, if n == 1 { "" } else { "s" });
-        let rx = lidar.start_scan().expect("Scan should have started");
+        lidar.start_scan().expect("Scan should have started");
         let window = create_window("scan", WindowOptions {
             // size: Some([WIDTH as u32 * 4 / 5, HEIGHT as u32 * 4 / 5]),
             fullscreen: false,
@@ -54,11 +54,10 @@ pub fn live_view() -> Result<(), Box<dyn Error>> {
 
         // generate pixel data from samples
         println!("Scanning...");
-        for (i, sample) in rx.iter().take(n).enumerate().tqdm() {
-            let raw_x = (sample.angle as f64).to_radians().cos() * sample.distance as f64;
-            let raw_y = (sample.angle as f64).to_radians().sin() * sample.distance as f64;
-            let x = ((raw_x / 5f64) as isize + (WIDTH as isize / 2)) as usize;
-            let y = ((raw_y / 5f64) as isize + (HEIGHT as isize / 2)) as usize;
+        for (i, sample) in lidar.samples().take(n).enumerate().tqdm() {
+            let (raw_x, raw_y) = sample.to_cartesian();
+            let x = ((raw_x / 5f32) as isize + (WIDTH as isize / 2)) as usize;
+            let y = ((raw_y / 5f32) as isize + (HEIGHT as isize / 2)) as usize;
 
             if x < WIDTH && y < HEIGHT {
                 let pos = (y * WIDTH + x) * 3;
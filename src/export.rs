@@ -0,0 +1,74 @@
+use crate::laser::protocol::Sample;
+use std::io;
+use std::io::Write;
+
+/// Writes a scan as CSV, one row per sample
+pub fn write_scan_csv<W: Write>(samples: &[Sample], w: &mut W) -> io::Result<()> {
+    writeln!(w, "angle_deg,distance_mm,intensity,start")?;
+    for sample in samples {
+        writeln!(w, "{},{},{},{}", sample.angle_deg(), sample.distance_mm(), sample.intensity, sample.start)?;
+    }
+    Ok(())
+}
+
+/// Writes a scan as an ASCII PCD v0.7 point cloud in millimeters, skipping zero-distance samples
+pub fn write_pcd<W: Write>(samples: &[Sample], w: &mut W) -> io::Result<()> {
+    let points: Vec<&Sample> = samples.iter().filter(|sample| sample.is_valid()).collect();
+
+    writeln!(w, "# .PCD v0.7 - Point Cloud Data file format")?;
+    writeln!(w, "VERSION 0.7")?;
+    writeln!(w, "FIELDS x y z intensity")?;
+    writeln!(w, "SIZE 4 4 4 1")?;
+    writeln!(w, "TYPE F F F U")?;
+    writeln!(w, "COUNT 1 1 1 1")?;
+    writeln!(w, "WIDTH {}", points.len())?;
+    writeln!(w, "HEIGHT 1")?;
+    writeln!(w, "VIEWPOINT 0 0 0 1 0 0 0")?;
+    writeln!(w, "POINTS {}", points.len())?;
+    writeln!(w, "DATA ascii")?;
+    for sample in points {
+        let (x, y) = sample.to_cartesian();
+        writeln!(w, "{} {} 0 {}", x, y, sample.intensity)?;
+    }
+    Ok(())
+}
+
+/// Distance between the range rings of [`scan_to_svg`] in millimeters
+const SVG_RING_SPACING_MM: u16 = 1000;
+
+/// Renders a scan as a square SVG polar plot `size_px` wide, centered on the lidar
+///
+/// Valid samples up to `max_mm` are drawn as dots, with a range ring every meter.
+pub fn scan_to_svg(samples: &[Sample], size_px: u32, max_mm: u16) -> String {
+    let center = size_px as f32 / 2.0;
+    let scale = center / max_mm.max(1) as f32;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size_px}\" height=\"{size_px}\" viewBox=\"0 0 {size_px} {size_px}\">\n"
+    );
+    svg.push_str(&format!("<rect width=\"{size_px}\" height=\"{size_px}\" fill=\"white\"/>\n"));
+    for ring_mm in (SVG_RING_SPACING_MM..=max_mm).step_by(SVG_RING_SPACING_MM as usize) {
+        svg.push_str(&format!(
+            "<circle cx=\"{center}\" cy=\"{center}\" r=\"{}\" fill=\"none\" stroke=\"lightgray\"/>\n",
+            ring_mm as f32 * scale
+        ));
+    }
+
+    for sample in samples.iter().filter(|sample| sample.is_valid() && sample.distance <= max_mm) {
+        let (x, y) = sample.to_cartesian();
+        // svg y grows downwards
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"1.5\" fill=\"red\"/>\n",
+            center + x * scale,
+            center - y * scale
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Serializes a scan as a JSON array of samples
+#[cfg(feature = "serde")]
+pub fn scan_to_json(samples: &[Sample]) -> String {
+    serde_json::to_string(samples).expect("Samples can be serialized")
+}
\ No newline at end of file
@@ -0,0 +1,113 @@
+//! C interface to the lidar
+//!
+//! [`rf_lidar_open`] returns a handle taken by all other functions, which must be released with [`rf_lidar_close`].
+
+use crate::laser::protocol::Sample;
+use crate::laser::Lidar;
+use std::ffi::{c_char, c_int, CStr};
+use std::ptr;
+
+/// C representation of a [`Sample`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RfSample {
+    pub start: bool,
+    pub intensity: u8,
+    /// angle in degrees, Q6 fixed point
+    pub angle_q6: u16,
+    /// distance in millimeters
+    pub distance: u16,
+}
+
+impl From<Sample> for RfSample {
+    fn from(sample: Sample) -> Self {
+        RfSample {
+            start: sample.start,
+            intensity: sample.intensity,
+            angle_q6: sample.angle_q6,
+            distance: sample.distance,
+        }
+    }
+}
+
+/// Opens the lidar on `port`, returning null on failure
+///
+/// # Safety
+///
+/// `port` must be a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_lidar_open(port: *const c_char) -> *mut Lidar {
+    if port.is_null() {
+        return ptr::null_mut();
+    }
+    // the string stays owned by the caller, it is copied here
+    let port = match CStr::from_ptr(port).to_str() {
+        Ok(port) => port.to_string(),
+        Err(_) => return ptr::null_mut(),
+    };
+
+    match Lidar::init(port) {
+        // ownership passes to the caller until rf_lidar_close
+        Ok(lidar) => Box::into_raw(Box::new(lidar)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Starts a standard scan, returning 0 on success and -1 on failure
+///
+/// # Safety
+///
+/// `lidar` must be a handle returned by [`rf_lidar_open`] that has not been closed.
+#[no_mangle]
+pub unsafe extern "C" fn rf_lidar_start_scan(lidar: *mut Lidar) -> c_int {
+    let Some(lidar) = lidar.as_mut() else {
+        return -1;
+    };
+
+    match lidar.start_scan() {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Copies up to `cap` buffered samples into `buf` without blocking, returning the number copied
+///
+/// # Safety
+///
+/// `lidar` must be a handle returned by [`rf_lidar_open`] that has not been closed,
+/// and `buf` must point to at least `cap` writable samples.
+#[no_mangle]
+pub unsafe extern "C" fn rf_lidar_get_samples(lidar: *mut Lidar, buf: *mut RfSample, cap: usize) -> usize {
+    let Some(lidar) = lidar.as_ref() else {
+        return 0;
+    };
+    if buf.is_null() {
+        return 0;
+    }
+
+    // the buffer stays owned by the caller, samples are copied into it
+    let mut count = 0;
+    while count < cap {
+        let Some(sample) = lidar.get_sample() else {
+            break;
+        };
+        buf.add(count).write(sample.into());
+        count += 1;
+    }
+    count
+}
+
+/// Stops any running scan and releases the handle
+///
+/// # Safety
+///
+/// `lidar` must be a handle returned by [`rf_lidar_open`] or null, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rf_lidar_close(lidar: *mut Lidar) {
+    if lidar.is_null() {
+        return;
+    }
+
+    // takes back ownership from the caller, dropping the lidar stops the scan without panicking
+    drop(Box::from_raw(lidar));
+}
\ No newline at end of file
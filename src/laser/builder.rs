@@ -0,0 +1,188 @@
+use crate::error::RxError;
+use crate::laser::lidar::{REQUEST_ATTEMPTS, S1_BAUD, SCAN_BUFFER_CAPACITY, SPIN_UP_DELAY};
+use crate::laser::cmd::SlLidarAnsType;
+use crate::laser::cmd::SlLidarCmd::GetDeviceInfo;
+use crate::laser::Lidar;
+use serialport::{ClearBuffer, SerialPort};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+/// Baud rates tried by [`LidarBuilder::auto_baud`], in order
+const PROBE_BAUDS: [u32; 3] = [256000, 115200, 1000000];
+
+/// Read timeout while probing a single baud rate
+const PROBE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Configures a [`Lidar`] before opening its serial connection
+#[derive(Debug, Clone)]
+pub struct LidarBuilder {
+    port: Option<String>,
+    baud: u32,
+    auto_baud: bool,
+    read_timeout: Duration,
+    buffer_capacity: usize,
+    spin_up_delay: Duration,
+    request_attempts: usize,
+}
+
+impl LidarBuilder {
+    pub fn new() -> LidarBuilder {
+        LidarBuilder {
+            port: None,
+            baud: S1_BAUD as u32,
+            auto_baud: false,
+            read_timeout: Duration::from_millis(1000),
+            buffer_capacity: SCAN_BUFFER_CAPACITY,
+            spin_up_delay: SPIN_UP_DELAY,
+            request_attempts: REQUEST_ATTEMPTS,
+        }
+    }
+
+    /// Serial port the lidar is connected to
+    pub fn port(mut self, port: String) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Baud rate of the serial connection, 256000 by default
+    pub fn baud(mut self, baud: u32) -> Self {
+        self.baud = baud;
+        self
+    }
+
+    /// Detects the baud rate when opening by probing the common rates for a device info answer
+    ///
+    /// Overrides [`baud`](Self::baud).
+    pub fn auto_baud(mut self) -> Self {
+        self.auto_baud = true;
+        self
+    }
+
+    /// Timeout for reads from the serial connection, one second by default
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Number of samples buffered before the oldest are dropped, at least one
+    pub fn buffer_capacity(mut self, buffer_capacity: usize) -> Self {
+        self.buffer_capacity = buffer_capacity.max(1);
+        self
+    }
+
+    /// Time the reader waits for the motor after starting a scan, one second by default
+    pub fn spin_up_delay(mut self, spin_up_delay: Duration) -> Self {
+        self.spin_up_delay = spin_up_delay;
+        self
+    }
+
+    /// Tries of an info, health or conf query before giving up on corrupt data or a timeout, three by default
+    pub fn request_attempts(mut self, request_attempts: usize) -> Self {
+        self.request_attempts = request_attempts;
+        self
+    }
+
+    /// Opens the serial connection to the lidar
+    pub fn open(self) -> Result<Lidar, RxError> {
+        let transport = self.open_transport()?;
+        let buffer_capacity = self.buffer_capacity;
+        let spin_up_delay = self.spin_up_delay;
+        let request_attempts = self.request_attempts;
+
+        let mut lidar = Lidar::from_transport(transport, buffer_capacity, Some(self));
+        lidar.spin_up_delay = spin_up_delay;
+        lidar.request_attempts = request_attempts;
+        Ok(lidar)
+    }
+
+    /// Opens the configured serial port without wrapping it
+    pub(crate) fn open_transport(&self) -> Result<Box<dyn SerialPort>, RxError> {
+        let port = self.port.as_ref().ok_or_else(|| {
+            serialport::Error::new(serialport::ErrorKind::InvalidInput, "no port given")
+        })?;
+        let port = if cfg!(windows) { normalize_com_port(port) } else { port.clone() };
+        if self.auto_baud {
+            return self.probe_baud(&port);
+        }
+
+        let transport = serialport::new(&port, self.baud)
+            .timeout(self.read_timeout)
+            .open()?;
+
+        Ok(transport)
+    }
+
+    /// Opens the port at the first baud rate the lidar answers a device info request on
+    fn probe_baud(&self, port: &str) -> Result<Box<dyn SerialPort>, RxError> {
+        for baud in PROBE_BAUDS {
+            let mut transport = serialport::new(port, baud).timeout(PROBE_TIMEOUT).open()?;
+            transport.clear(ClearBuffer::All)?;
+            transport.write_all(&[0xa5, GetDeviceInfo as u8])?;
+
+            let mut descriptor = [0u8; 7];
+            if transport.read_exact(&mut descriptor).is_err()
+                || descriptor[0..2] != [0xa5, 0x5a]
+                || descriptor[6] != SlLidarAnsType::DevInfo as u8
+            {
+                continue;
+            }
+
+            // discard the device info itself, a short answer means the rate is wrong too
+            let mut info = [0u8; 20];
+            if transport.read_exact(&mut info).is_err() {
+                continue;
+            }
+            transport.set_timeout(self.read_timeout)?;
+            return Ok(transport);
+        }
+
+        Err(RxError::TimedOut)
+    }
+}
+
+/// Prefixes Windows COM ports above 9 with `\\.\`, which they can only be opened by
+fn normalize_com_port(port: &str) -> String {
+    let high_com_port = port.get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("COM"))
+        && port[3..].bytes().all(|b| b.is_ascii_digit())
+        && port[3..].parse::<u32>().is_ok_and(|n| n > 9);
+    if high_com_port {
+        format!(r"\\.\{}", port)
+    } else {
+        port.to_string()
+    }
+}
+
+impl Default for LidarBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_com_ports_are_unchanged() {
+        assert_eq!(normalize_com_port("COM3"), "COM3");
+    }
+
+    #[test]
+    fn high_com_ports_are_prefixed() {
+        assert_eq!(normalize_com_port("COM10"), r"\\.\COM10");
+        assert_eq!(normalize_com_port("com12"), r"\\.\com12");
+    }
+
+    #[test]
+    fn other_names_are_unchanged() {
+        assert_eq!(normalize_com_port(r"\\.\COM10"), r"\\.\COM10");
+        assert_eq!(normalize_com_port("COMX"), "COMX");
+        assert_eq!(normalize_com_port("COM+10"), "COM+10");
+        assert_eq!(normalize_com_port("/dev/ttyUSB0"), "/dev/ttyUSB0");
+    }
+
+    #[test]
+    fn buffer_capacity_is_at_least_one() {
+        assert_eq!(LidarBuilder::new().buffer_capacity(0).buffer_capacity, 1);
+    }
+}
\ No newline at end of file
@@ -1,3 +1,5 @@
+use std::fmt;
+
 // Commands
 // pub const DEFAULT_MOTOR_SPEED: u16 = 0xFFFF;
 // const SL_LIDAR_AUTOBAUD_MAGICBYTE: u8 = 0x41;
@@ -64,6 +66,13 @@ pub enum SlLidarCmd {
 // struct SlLidarPayloadMotorPwmT {
 //     pwm_value: u16,
 // }
+
+/// Lowest motor PWM duty cycle accepted by `SetMotorPWM`, stops the motor
+pub const MOTOR_PWM_MIN: u16 = 0;
+/// Highest motor PWM duty cycle accepted by `SetMotorPWM`
+pub const MOTOR_PWM_MAX: u16 = 1023;
+/// Motor PWM duty cycle the vendor SDK scans at
+pub const MOTOR_PWM_DEFAULT: u16 = 660;
 //
 // struct SlLidarPayloadAccBoardFlagT {
 //     reserved: u32,
@@ -79,7 +88,7 @@ pub enum SlLidarCmd {
 //     param: u16,
 // }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(u8)]
 pub enum SlLidarAnsType {
     DevInfo = 0x04,
@@ -99,11 +108,43 @@ pub enum SlLidarAnsType {
     AccBoardFlag = 0xFF,
 }
 
-// const SL_LIDAR_RESP_ACC_BOARD_FLAG_MOTOR_CTRL_SUPPORT_MASK: u8 = 0x1;
-//
-// struct SlLidarResponseAccBoardFlagT {
-//     support_flag: u32,
-// }
+impl TryFrom<u8> for SlLidarAnsType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        use SlLidarAnsType::*;
+        Ok(match value {
+            0x04 => DevInfo,
+            0x06 => DevHealth,
+            0x81 => Measurement,
+            0x82 => MeasurementCapsuled,
+            0x83 => MeasurementHQ,
+            0x84 => MeasurementCapsuledUltra,
+            0x85 => MeasurementDenseCapsuled,
+            0x86 => MeasurementUltraDenseCapsuled,
+            0x15 => SampleRate,
+            0x20 => GetLidarConf,
+            0x21 => SetLidarConf,
+            0xFF => AccBoardFlag,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// Bit of the accessory board flag set when the motor speed can be controlled by PWM
+pub const ACC_BOARD_FLAG_MOTOR_CTRL_SUPPORT_MASK: u32 = 0x1;
+
+/// How the lidar's motor speed can be controlled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotorCtrlSupport {
+    /// the motor runs at a fixed speed
+    None,
+    /// the accessory board drives the motor by PWM duty cycle
+    Pwm,
+    /// the lidar regulates the motor to a target RPM
+    Rpm,
+}
+
 //
 // enum SlLidarStatus {
 //     Ok = 0x0,
@@ -119,9 +160,25 @@ pub enum SlLidarAnsType {
 // const SL_LIDAR_RESP_MEASUREMENT_CHECKBIT: u8 = 0x01;
 // const SL_LIDAR_RESP_MEASUREMENT_ANGLE_SHIFT: u8 = 0x01;
 
-pub(crate) struct SlLidarResponseSampleRateT {
-    pub(crate) std_sample_duration_us: u16,
-    pub(crate) express_sample_duration_us: u16,
+#[derive(Debug)]
+pub struct SlLidarResponseSampleRateT {
+    pub std_sample_duration_us: u16,
+    pub express_sample_duration_us: u16,
+}
+
+impl SlLidarResponseSampleRateT {
+    /// Estimated number of samples per revolution at the given motor speed
+    pub fn points_per_rev(&self, rpm: u16, express: bool) -> u32 {
+        let duration_us = if express {
+            self.express_sample_duration_us
+        } else {
+            self.std_sample_duration_us
+        };
+        if rpm == 0 || duration_us == 0 {
+            return 0;
+        }
+        60_000_000 / (rpm as u32 * duration_us as u32)
+    }
 }
 
 // struct SlLidarResponseMeasurementNodeT {
@@ -216,12 +273,10 @@ pub(crate) struct SlLidarResponseSampleRateT {
 // const SL_LIDAR_CONF_SCAN_COMMAND_SENSITIVITY: u8 = 5;
 //
 // const SL_LIDAR_CONF_ANGLE_RANGE: u8 = 0x00000000;
-// const SL_LIDAR_CONF_DESIRED_ROT_FREQ: u8 = 0x00000001;
 // const SL_LIDAR_CONF_SCAN_COMMAND_BITMAP: u8 = 0x00000002;
 // const SL_LIDAR_CONF_MIN_ROT_FREQ: u8 = 0x00000004;
 // const SL_LIDAR_CONF_MAX_ROT_FREQ: u8 = 0x00000005;
 // const SL_LIDAR_CONF_MAX_DISTANCE: u8 = 0x00000060;
-// const SL_LIDAR_CONF_LIDAR_MAC_ADDR: u32 = 0x00000079;
 
 #[repr(u32)]
 #[derive(Clone, Copy)]
@@ -232,6 +287,10 @@ pub enum ScanModeConfEntry {
     AnsType = 0x00000075,
     Typical = 0x0000007C,
     Name = 0x0000007F,
+
+    // not scan mode specific
+    DesiredRotFreq = 0x00000001,
+    LidarMacAddr = 0x00000079,
 }
 
 // const SL_LIDAR_CONF_MODEL_REVISION_ID: u32 = 0x00000080;
@@ -243,9 +302,9 @@ pub enum ScanModeConfEntry {
 // const SL_LIDAR_EXPRESS_SCAN_STABILITY_BITMAP: u8 = 4;
 // const SL_LIDAR_EXPRESS_SCAN_SENSITIVITY_BITMAP: u8 = 5;
 
-pub(crate) struct SlLidarResponseGetLidarConf {
-    pub(crate) conf_type: u32,
-    pub(crate) payload: Vec<u8>,
+pub struct SlLidarResponseGetLidarConf {
+    pub conf_type: u32,
+    pub payload: Vec<u8>,
 }
 
 // struct SlLidarResponseSetLidarConf {
@@ -253,6 +312,8 @@ pub(crate) struct SlLidarResponseGetLidarConf {
 //     result: u32,
 // }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug)]
 pub struct SlLidarResponseDeviceInfoT {
     pub model: u8,
     pub firmware_version: u16,
@@ -260,9 +321,106 @@ pub struct SlLidarResponseDeviceInfoT {
     pub serial_number: [u8; 16],
 }
 
+/// Lidar model family, decoded from the device info model id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LidarModel {
+    A1,
+    A2,
+    A3,
+    S1,
+    S2,
+    C1,
+    Unknown(u8),
+}
+
+impl SlLidarResponseDeviceInfoT {
+    /// Decodes the model family from the major model id (upper nibble of `model`)
+    pub fn model_kind(&self) -> LidarModel {
+        match self.model >> 4 {
+            0x1 => LidarModel::A1,
+            0x2 => LidarModel::A2,
+            0x3 => LidarModel::A3,
+            0x4 => LidarModel::C1,
+            0x6 => LidarModel::S1,
+            0x7 => LidarModel::S2,
+            _ => LidarModel::Unknown(self.model),
+        }
+    }
+
+    /// Serial number as uppercase hex, in the byte order the vendor tools display
+    pub fn serial_hex(&self) -> String {
+        self.serial_number.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    /// Firmware version as (major, minor)
+    pub fn firmware_semver(&self) -> (u8, u8) {
+        ((self.firmware_version >> 8) as u8, self.firmware_version as u8)
+    }
+}
+
+/// Formats as e.g. `A1 (model 0x18), firmware v1.29, hardware 7`
+impl fmt::Display for SlLidarResponseDeviceInfoT {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (major, minor) = self.firmware_semver();
+        write!(
+            f,
+            "{:?} (model {:#04x}), firmware v{}.{:02}, hardware {}",
+            self.model_kind(),
+            self.model,
+            major,
+            minor,
+            self.hardware_version
+        )
+    }
+}
+
+impl LidarModel {
+    /// Maximum range of the model in millimeters, beyond which returns are unreliable
+    pub fn max_distance_mm(&self) -> u16 {
+        match self {
+            LidarModel::A1 | LidarModel::C1 => 12000,
+            LidarModel::A2 => 16000,
+            LidarModel::A3 => 25000,
+            LidarModel::S2 => 30000,
+            LidarModel::S1 => 40000,
+            LidarModel::Unknown(_) => u16::MAX,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug)]
 pub struct SlLidarResponseDeviceHealthT {
-    pub(crate) status: u8,
-    pub(crate) error_code: u16,
+    pub status: u8,
+    pub error_code: u16,
+}
+
+/// Health status reported by the lidar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl SlLidarResponseDeviceHealthT {
+    /// Decodes the status byte
+    pub fn health_status(&self) -> HealthStatus {
+        match self.status {
+            0 => HealthStatus::Ok,
+            1 => HealthStatus::Warning,
+            _ => HealthStatus::Error,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.health_status() == HealthStatus::Ok
+    }
+
+    /// Whether the lidar has to be reset before it can scan again
+    pub fn needs_reset(&self) -> bool {
+        self.health_status() == HealthStatus::Error
+    }
 }
 
 // struct SlLidarIpConfT {
@@ -270,10 +428,12 @@ pub struct SlLidarResponseDeviceHealthT {
 //     net_mask: [u8; 4],
 //     gw: [u8; 4],
 // }
-//
-// struct SlLidarResponseDeviceMacaddrInfoT {
-//     macaddr: [u8; 6],
-// }
+
+/// Device information including the unique id reported by newer firmware
+pub struct SlLidarResponseDeviceInfoExtendedT {
+    pub info: SlLidarResponseDeviceInfoT,
+    pub macaddr: [u8; 6],
+}
 
 // struct SlLidarResponseDesiredRotSpeedT {
 //     rpm: u16,
@@ -0,0 +1,421 @@
+use crate::error::RxError;
+use crate::laser::cmd::SlLidarAnsType;
+use crate::laser::cmd::SlLidarAnsType::{Measurement, MeasurementCapsuled, MeasurementCapsuledUltra, MeasurementDenseCapsuled};
+use crate::laser::protocol;
+use crate::laser::protocol::Sample;
+use std::f64::consts::PI;
+use std::time::Instant;
+
+/// Default number of consecutive corrupt messages after which the decoder realigns byte by byte
+pub(crate) const RESYNC_THRESHOLD: usize = 3;
+
+/// Assembles samples from the messages of a scan stream, independent of how the bytes are received
+///
+/// Bytes following the response descriptor are fed in as they arrive. Messages straddling two
+/// pushes are buffered until complete, and samples before the first start of a revolution are skipped.
+/// Samples without a return are emitted with a distance of 0, only corrupt messages are discarded.
+pub struct ScanDecoder {
+    ans_type: SlLidarAnsType,
+    msg_size: usize,
+    /// consecutive corrupt messages before realigning
+    resync_threshold: usize,
+    /// bytes not yet decoded
+    pending: Vec<u8>,
+    /// last valid capsule, whose angles are interpolated once the next one arrives
+    prev_capsule: Option<Vec<u8>>,
+    /// no revolution has started yet
+    seeking: bool,
+    /// consecutive corrupt messages so far
+    failures: usize,
+    /// corrupt messages discarded so far
+    dropped: usize,
+}
+
+impl ScanDecoder {
+    /// Creates a decoder for messages of the given answer type
+    ///
+    /// Fails with [`RxError::OperationNotSupport`] if there is no decoder for it.
+    pub fn new(ans_type: SlLidarAnsType) -> Result<ScanDecoder, RxError> {
+        let msg_size = message_size(ans_type).ok_or(RxError::OperationNotSupport)?;
+        Ok(ScanDecoder {
+            ans_type,
+            msg_size,
+            resync_threshold: RESYNC_THRESHOLD,
+            pending: Vec::new(),
+            prev_capsule: None,
+            seeking: true,
+            failures: 0,
+            dropped: 0,
+        })
+    }
+
+    /// Sets how many consecutive corrupt messages are tolerated before realigning to the stream byte by byte
+    pub fn set_resync_threshold(&mut self, threshold: usize) {
+        self.resync_threshold = threshold;
+    }
+
+    /// Size of a single message in bytes
+    pub fn message_size(&self) -> usize {
+        self.msg_size
+    }
+
+    /// Number of corrupt messages discarded so far
+    pub fn samples_dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Whether the decoder is still waiting for the first start of a revolution
+    pub fn is_seeking(&self) -> bool {
+        self.seeking
+    }
+
+    /// Decodes the complete messages among the buffered and given bytes, timestamped now
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Vec<Sample> {
+        self.push_bytes_at(bytes, Instant::now())
+    }
+
+    /// Decodes the complete messages among the buffered and given bytes, timestamped `received`
+    pub fn push_bytes_at(&mut self, bytes: &[u8], received: Instant) -> Vec<Sample> {
+        self.pending.extend_from_slice(bytes);
+
+        let msg_size = self.msg_size;
+        let mut samples = Vec::new();
+        let mut offset = 0;
+        while self.pending.len() - offset >= msg_size {
+            let slice = &self.pending[offset..offset + msg_size];
+            let valid = match self.ans_type {
+                Measurement => match decode_standard_sample(slice.try_into().unwrap(), received) {
+                    Some(sample) => {
+                        samples.push(sample);
+                        true
+                    }
+                    None => false,
+                },
+                _ => {
+                    // checks
+                    if protocol::validate_capsule(slice) {
+                        // a set start bit means the stream (re)started, so there is nothing to interpolate against
+                        if slice[3] & 0x80 == 0 {
+                            if let Some(prev) = &self.prev_capsule {
+                                match self.ans_type {
+                                    MeasurementCapsuled => decode_express_capsule(prev, slice, received, &mut samples),
+                                    MeasurementDenseCapsuled => decode_dense_capsule(prev, slice, received, &mut samples),
+                                    _ => decode_ultra_capsule(prev, slice, received, &mut samples),
+                                }
+                            }
+                        }
+                        self.prev_capsule = Some(slice.to_vec());
+                        true
+                    } else {
+                        self.prev_capsule = None;
+                        false
+                    }
+                }
+            };
+
+            if valid {
+                self.failures = 0;
+                offset += msg_size;
+                continue;
+            }
+
+            self.failures += 1;
+            if self.failures < self.resync_threshold {
+                log::debug!("Discarding corrupt message {:02x?}", slice);
+                self.dropped += 1;
+                offset += msg_size;
+            } else {
+                // the stream is likely misaligned, look for the next valid message
+                if self.failures == self.resync_threshold {
+                    log::warn!("Lidar stream out of sync, realigning");
+                    self.dropped += 1;
+                }
+                offset += 1;
+            }
+        }
+        self.pending.drain(..offset);
+
+        // leading samples of a partial revolution are skipped
+        if self.seeking {
+            let first = samples.iter().position(|sample| sample.start).unwrap_or(samples.len());
+            samples.drain(..first);
+            self.seeking = samples.is_empty();
+        }
+        samples
+    }
+}
+
+/// Decodes a standard scan message, or returns `None` if its check bits are wrong
+fn decode_standard_sample(data: &[u8; 5], received: Instant) -> Option<Sample> {
+    // checks
+    let s = data[0] & 0b11;
+    if s == 0b11 || s == 0b00 || data[1] & 0b01 != 1 {
+        return None;
+    }
+
+    Some(Sample {
+        start: (data[0] & 1) != 0,
+        intensity: data[0] >> 2,
+        angle_q6: ((data[2] as u16) << 7) | (data[1] as u16 >> 1),
+        distance: (((data[4] as u16) << 8) | data[3] as u16) / 4,
+        timestamp: received,
+    })
+}
+
+/// Start angle of a capsule in Q8 degrees
+fn capsule_start_angle_q8(capsule: &[u8]) -> i32 {
+    ((((capsule[3] & 0x7f) as i32) << 8) | capsule[2] as i32) << 2
+}
+
+/// Angle covered by the `prev` capsule in Q8 degrees
+fn capsule_angle_diff_q8(prev: &[u8], cur: &[u8]) -> i32 {
+    let diff_q8 = capsule_start_angle_q8(cur) - capsule_start_angle_q8(prev);
+    if diff_q8 < 0 {
+        diff_q8 + (360 << 8)
+    } else {
+        diff_q8
+    }
+}
+
+/// Decodes the cabins of the `prev` express capsule, interpolating their
+/// angles up to the start angle of `cur`
+///
+/// `prev` was validated when it was received.
+fn decode_express_capsule(prev: &[u8], cur: &[u8], received: Instant, out: &mut Vec<Sample>) {
+    let capsule = protocol::parse_capsule(prev.try_into().unwrap());
+
+    // 32 samples per capsule
+    let angle_inc_q16 = capsule_angle_diff_q8(prev, cur) << 3;
+    let mut angle_q16 = (capsule.start_angle_q6 as i32) << 10;
+
+    for cabin in capsule.cabins {
+        for (distance, offset_q3) in cabin.distances.into_iter().zip(cabin.offsets_q3) {
+            let angle_q6 = (angle_q16 - ((offset_q3 as i32) << 13)) >> 10;
+            let start = (angle_q16 + angle_inc_q16) % (360 << 16) < angle_inc_q16;
+            angle_q16 += angle_inc_q16;
+
+            out.push(Sample {
+                start,
+                intensity: if distance != 0 { 0x2f } else { 0 },
+                angle_q6: angle_q6.rem_euclid(360 << 6) as u16,
+                distance,
+                timestamp: received,
+            });
+        }
+    }
+}
+
+/// Decodes the cabins of the `prev` dense capsule, interpolating their
+/// angles up to the start angle of `cur`
+fn decode_dense_capsule(prev: &[u8], cur: &[u8], received: Instant, out: &mut Vec<Sample>) {
+    // 40 samples per capsule
+    let angle_inc_q16 = (capsule_angle_diff_q8(prev, cur) << 8) / 40;
+    let mut angle_q16 = capsule_start_angle_q8(prev) << 8;
+
+    for cabin in prev[4..].chunks_exact(2) {
+        let angle_q6 = angle_q16 >> 10;
+        let start = (angle_q16 + angle_inc_q16) % (360 << 16) < angle_inc_q16;
+        angle_q16 += angle_inc_q16;
+
+        let distance = u16::from_le_bytes([cabin[0], cabin[1]]);
+        out.push(Sample {
+            start,
+            intensity: if distance != 0 { 0x2f } else { 0 },
+            angle_q6: angle_q6.rem_euclid(360 << 6) as u16,
+            distance,
+            timestamp: received,
+        });
+    }
+}
+
+/// Expands a variable bit scaled ultra capsule distance, returning it with its scale level
+fn varbitscale_decode(scaled: u32) -> (u32, u32) {
+    const SCALED_BASE: [u32; 5] = [3328, 1792, 1280, 512, 0];
+    const SCALE_LEVEL: [u32; 5] = [4, 3, 2, 1, 0];
+    const TARGET_BASE: [u32; 5] = [1 << 14, 1 << 12, 1 << 11, 1 << 9, 0];
+
+    for ((base, level), target) in SCALED_BASE.into_iter().zip(SCALE_LEVEL).zip(TARGET_BASE) {
+        if scaled >= base {
+            return (target + ((scaled - base) << level), level);
+        }
+    }
+    (0, 0)
+}
+
+/// Decodes the cabins of the `prev` ultra capsule, interpolating their
+/// angles up to the start angle of `cur`
+fn decode_ultra_capsule(prev: &[u8], cur: &[u8], received: Instant, out: &mut Vec<Sample>) {
+    // 32 cabins of 3 samples each
+    let angle_inc_q16 = (capsule_angle_diff_q8(prev, cur) << 3) / 3;
+    let mut angle_q16 = capsule_start_angle_q8(prev) << 8;
+
+    let cabins: Vec<u32> = prev[4..]
+        .chunks_exact(4)
+        .map(|cabin| u32::from_le_bytes(cabin.try_into().unwrap()))
+        .collect();
+    let next_major = u32::from_le_bytes(cur[4..8].try_into().unwrap()) & 0xfff;
+
+    for (i, &combined) in cabins.iter().enumerate() {
+        // the second and third samples are predicted from this cabin's and the next cabin's major distance
+        let (major, mut scale1) = varbitscale_decode(combined & 0xfff);
+        let (major2, scale2) = varbitscale_decode(cabins.get(i + 1).map_or(next_major, |next| next & 0xfff));
+        let mut base1 = major;
+        if major == 0 && major2 != 0 {
+            base1 = major2;
+            scale1 = scale2;
+        }
+
+        // signed 10 bit offsets, the extremes mark a missing return
+        let predict = |offset: i32, base: u32, scale: u32| -> u16 {
+            if offset == -512 || offset == 0x1ff {
+                return 0;
+            }
+            ((offset << scale) + base as i32).max(0) as u16
+        };
+        let distances = [
+            major as u16,
+            predict(((combined << 10) as i32) >> 22, base1, scale1),
+            predict((combined as i32) >> 22, major2, scale2),
+        ];
+
+        for distance in distances {
+            // compensates the offset of the ranging optics from the rotation center
+            let offset_rad_q16 = if distance >= 50 {
+                let k2 = 98361 / (distance as i32 * 4);
+                (8.0 * PI * 65536.0 / 180.0) as i32 - (k2 << 6) - (k2 * k2 * k2) / 98304
+            } else {
+                (7.5 * PI * 65536.0 / 180.0) as i32
+            };
+            let angle_q6 = (angle_q16 - (offset_rad_q16 as f64 * 180.0 / PI) as i32) >> 10;
+            let start = (angle_q16 + angle_inc_q16) % (360 << 16) < angle_inc_q16;
+            angle_q16 += angle_inc_q16;
+
+            out.push(Sample {
+                start,
+                intensity: if distance != 0 { 0x2f } else { 0 },
+                angle_q6: angle_q6.rem_euclid(360 << 6) as u16,
+                distance,
+                timestamp: received,
+            });
+        }
+    }
+}
+
+/// Size of a single message of the given answer type, or `None` if there is no decoder for it
+pub(crate) fn message_size(ans_type: SlLidarAnsType) -> Option<usize> {
+    match ans_type {
+        Measurement => Some(5),
+        MeasurementCapsuled | MeasurementDenseCapsuled => Some(84),
+        MeasurementCapsuledUltra => Some(132),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::laser::Lidar;
+
+    /// Start of a revolution at 10.5° and 1000 mm with quality 47
+    const START_FRAME: [u8; 5] = [0xbd, 0x41, 0x05, 0xa0, 0x0f];
+
+    #[test]
+    fn decodes_standard_frame() {
+        let sample = decode_standard_sample(&START_FRAME, Instant::now()).unwrap();
+        assert!(sample.start);
+        assert_eq!(sample.intensity, 47);
+        assert_eq!(sample.angle_deg(), 10.5);
+        assert_eq!(sample.distance, 1000);
+    }
+
+    #[test]
+    fn rejects_bad_check_bits() {
+        // start and inverted start both set
+        let mut frame = START_FRAME;
+        frame[0] |= 0b11;
+        assert!(decode_standard_sample(&frame, Instant::now()).is_none());
+
+        // check bit cleared
+        let mut frame = START_FRAME;
+        frame[1] &= !1;
+        assert!(decode_standard_sample(&frame, Instant::now()).is_none());
+    }
+
+    #[test]
+    fn assembles_frame_split_across_pushes() {
+        let mut decoder = ScanDecoder::new(Measurement).unwrap();
+        assert!(decoder.push_bytes(&START_FRAME[..2]).is_empty());
+
+        let samples = decoder.push_bytes(&START_FRAME[2..]);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].angle_q6, 672);
+        assert_eq!(samples[0].distance, 1000);
+        assert_eq!(decoder.samples_dropped(), 0);
+    }
+    /// Sets the start angle, sync nibbles and checksum of a capsule with the given cabins
+    fn capsule<const N: usize>(start_angle_q6: u16, cabins: &[u8]) -> [u8; N] {
+        let mut capsule = [0; N];
+        capsule[2..4].copy_from_slice(&start_angle_q6.to_le_bytes());
+        capsule[4..4 + cabins.len()].copy_from_slice(cabins);
+        let checksum = Lidar::checksum(&capsule[2..]);
+        capsule[0] = 0xa0 | (checksum & 0xf);
+        capsule[1] = 0x50 | (checksum >> 4);
+        capsule
+    }
+
+    #[test]
+    fn decodes_express_capsule() {
+        // 1000 mm 2° behind and 2000 mm at the interpolated angle
+        let prev: [u8; 84] = capsule(10 << 6, &[0xa1, 0x0f, 0x40, 0x1f, 0x00]);
+        let cur: [u8; 84] = capsule(42 << 6, &[]);
+        let mut samples = Vec::new();
+        decode_express_capsule(&prev, &cur, Instant::now(), &mut samples);
+
+        assert_eq!(samples.len(), 32);
+        assert_eq!((samples[0].angle_q6, samples[0].distance), (8 << 6, 1000));
+        assert_eq!((samples[1].angle_q6, samples[1].distance), (11 << 6, 2000));
+        assert_eq!((samples[2].angle_q6, samples[2].distance, samples[2].intensity), (12 << 6, 0, 0));
+        assert!(samples.iter().all(|sample| !sample.start));
+    }
+
+    #[test]
+    fn decodes_dense_capsule() {
+        let prev: [u8; 84] = capsule(10 << 6, &1500u16.to_le_bytes());
+        let cur: [u8; 84] = capsule(50 << 6, &[]);
+        let mut samples = Vec::new();
+        decode_dense_capsule(&prev, &cur, Instant::now(), &mut samples);
+
+        assert_eq!(samples.len(), 40);
+        assert_eq!((samples[0].angle_q6, samples[0].distance, samples[0].intensity), (10 << 6, 1500, 0x2f));
+        assert_eq!((samples[1].angle_q6, samples[1].distance, samples[1].intensity), (11 << 6, 0, 0));
+        assert_eq!(samples[39].angle_q6, 49 << 6);
+    }
+
+    #[test]
+    fn decodes_ultra_capsule() {
+        // major distance 300 mm, the second sample 5 mm further and the third missing
+        let combined: u32 = 300 | (5 << 12) | (0x1ff << 22);
+        let prev: [u8; 132] = capsule(10 << 6, &combined.to_le_bytes());
+        let cur: [u8; 132] = capsule(106 << 6, &[]);
+        let mut samples = Vec::new();
+        decode_ultra_capsule(&prev, &cur, Instant::now(), &mut samples);
+
+        assert_eq!(samples.len(), 96);
+        let distances: Vec<u16> = samples[..3].iter().map(|sample| sample.distance).collect();
+        assert_eq!(distances, [300, 305, 0]);
+        // samples without a return sit 7.5° behind their interpolated angle
+        assert_eq!(samples[2].angle_q6, 288);
+    }
+
+    #[test]
+    fn decoder_drops_capsule_with_bad_checksum() {
+        let mut decoder = ScanDecoder::new(MeasurementCapsuled).unwrap();
+        let mut corrupt: [u8; 84] = capsule(10 << 6, &[0xa1, 0x0f, 0x40, 0x1f, 0x00]);
+        corrupt[10] ^= 0xff;
+
+        assert!(decoder.push_bytes(&corrupt).is_empty());
+        assert_eq!(decoder.samples_dropped(), 1);
+        // the capsule is not kept to interpolate against
+        assert!(decoder.prev_capsule.is_none());
+    }
+}
\ No newline at end of file
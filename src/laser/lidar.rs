@@ -1,21 +1,145 @@
 use crate::error::RxError;
 use crate::laser::cmd::ScanModeConfEntry::*;
-use crate::laser::cmd::SlLidarCmd::{ExpressScan, GetDeviceHealth, GetDeviceInfo, GetLidarConf, GetSampleRate, HQMotorSpeedCtrl, Reset, Scan, Stop};
+use crate::laser::cmd::SlLidarCmd::{ExpressScan, ForceScan, GetAccBoardFlag, GetDeviceHealth, GetDeviceInfo, GetLidarConf, GetSampleRate, HQMotorSpeedCtrl, Reset, Scan, SetMotorPWM, Stop};
+use crate::laser::cmd::SlLidarAnsType::{Measurement, MeasurementCapsuled, MeasurementDenseCapsuled};
 use crate::laser::cmd::{
-    ScanModeConfEntry, SlLidarResponseDeviceHealthT, SlLidarResponseDeviceInfoT,
+    HealthStatus, SlLidarCmd, LidarModel, MotorCtrlSupport, ScanModeConfEntry, ACC_BOARD_FLAG_MOTOR_CTRL_SUPPORT_MASK, MOTOR_PWM_DEFAULT, MOTOR_PWM_MAX, MOTOR_PWM_MIN, SlLidarAnsType, SlLidarResponseDeviceHealthT, SlLidarResponseDeviceInfoExtendedT, SlLidarResponseDeviceInfoT,
     SlLidarResponseGetLidarConf, SlLidarResponseSampleRateT,
 };
+use crate::laser::decoder::{message_size, RESYNC_THRESHOLD};
 use crate::laser::protocol::{Response, ResponseDescriptor, Sample};
-use serialport::SerialPort;
+use crate::laser::{Diagnostics, LidarBuilder, ScanDecoder, ScanMode};
+use serialport::{ClearBuffer, SerialPort};
+use std::fs::File;
+use std::io;
 use std::io::{Read, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{Receiver, Sender};
-use std::sync::{mpsc, Arc};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 use std::thread::{sleep, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-const S1_BAUD: usize = 256000;
+pub(crate) const S1_BAUD: usize = 256000;
+
+/// Default time the reader waits for the motor after starting a scan
+pub(crate) const SPIN_UP_DELAY: Duration = Duration::from_millis(1000);
+
+/// Default number of tries of a query before giving up
+pub(crate) const REQUEST_ATTEMPTS: usize = 3;
+
+/// Wait before retrying a failed query, multiplied by the number of failures so far
+const RETRY_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Default maximum number of buffered samples before the oldest are dropped
+pub(crate) const SCAN_BUFFER_CAPACITY: usize = 8192;
+
+/// What the lidar is currently doing
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LidarState {
+    Idle = 0,
+    Scanning = 1,
+    /// The lidar stalled or reported an error, and must be reset before scanning again
+    ProtectionStop = 2,
+}
+
+impl From<LidarState> for u8 {
+    fn from(state: LidarState) -> u8 {
+        state as u8
+    }
+}
+
+impl From<u8> for LidarState {
+    fn from(value: u8) -> LidarState {
+        match value {
+            1 => LidarState::Scanning,
+            2 => LidarState::ProtectionStop,
+            _ => LidarState::Idle,
+        }
+    }
+}
+
+/// Reads a state shared with the reader thread
+fn load_state(state: &AtomicU8) -> LidarState {
+    state.load(Ordering::Acquire).into()
+}
+
+/// Replaces a state shared with the reader thread, returning the previous one
+fn swap_state(state: &AtomicU8, new: LidarState) -> LidarState {
+    state.swap(new.into(), Ordering::AcqRel).into()
+}
+
+/// Replaces a state shared with the reader thread if it is `current`
+fn transition_state(state: &AtomicU8, current: LidarState, new: LidarState) -> bool {
+    state
+        .compare_exchange(current.into(), new.into(), Ordering::AcqRel, Ordering::Acquire)
+        .is_ok()
+}
+
+/// What the reader thread does with new samples when the scan buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// discards the oldest buffered sample
+    #[default]
+    DropOldest,
+    /// discards the new sample
+    DropNewest,
+    /// waits for samples to be consumed, the lidar's own buffer may overflow in the meantime
+    Block,
+}
+
+/// Counters accumulated by the reader thread since the current scan started
+#[derive(Debug, Clone, Default)]
+pub struct ScanStats {
+    /// samples decoded
+    pub samples_total: usize,
+    /// corrupt messages discarded
+    pub samples_dropped: usize,
+    /// completed revolutions
+    pub revolutions: usize,
+    /// when the reader thread last received data
+    pub last_update: Option<Instant>,
+    /// when the reader thread last received the start of a revolution
+    pub last_revolution_start: Option<Instant>,
+    /// measured duration of a revolution
+    pub revolution_period: Option<Duration>,
+}
+
+/// Longest a managed scan waits for the motor to reach its target speed
+const MOTOR_SPIN_UP: Duration = Duration::from_millis(3000);
+
+/// How long the lidar must report itself healthy before the motor is assumed stable, health does not report the motor speed
+const MOTOR_SETTLE: Duration = Duration::from_millis(200);
+
+/// Longest a consumer waits for a sample before checking whether the scan stopped
+const SAMPLE_WAIT: Duration = Duration::from_millis(100);
+
+/// Settings of a reader thread, fixed when the scan starts unless noted
+struct ReaderConfig {
+    ans_type: SlLidarAnsType,
+    /// maximum number of buffered samples
+    capacity: usize,
+    /// what to do when `capacity` is reached
+    policy: OverflowPolicy,
+    /// time to wait for the motor before decoding
+    spin_up_delay: Duration,
+    /// samples further than this many millimeters are marked invalid
+    max_distance: u16,
+    /// added to every sample's angle, in degrees * 64 within one revolution
+    angle_offset_q6: u16,
+    /// consecutive corrupt messages before realigning
+    resync_threshold: usize,
+    /// file receiving a copy of the raw stream
+    recording: Option<File>,
+    /// buffer receiving undecoded standard scan messages instead of decoding them
+    raw_buffer: Option<Arc<Mutex<VecDeque<[u8; 5]>>>>,
+    /// notified when messages are pushed to `raw_buffer`
+    raw_ready: Arc<Condvar>,
+    /// discards decoded samples while set, toggled during the scan
+    paused: Arc<AtomicBool>,
+}
 
 /// Represents a serial connection to a lidar
 pub struct Lidar {
@@ -24,38 +148,171 @@ pub struct Lidar {
 
     /// reader thread handle
     thread_handle: Option<JoinHandle<()>>,
-    /// Should nuke `reader_thread`?
-    nuke: Arc<AtomicBool>,
+    /// current state, `reader_thread` exits once this leaves `Scanning`
+    state: Arc<AtomicU8>,
+    /// samples decoded by `reader_thread`, oldest first
+    scan_buffer: Arc<Mutex<VecDeque<Sample>>>,
+    /// notified by `reader_thread` when samples are pushed to `scan_buffer` or the scan ends
+    sample_ready: Arc<Condvar>,
+    /// revolution left incomplete by a timed out `get_full_scan_timeout`, continued by the next call
+    partial_scan: Mutex<Vec<Sample>>,
+    /// counters for the current scan
+    stats: Arc<Mutex<ScanStats>>,
+    /// number of times the lidar entered `ProtectionStop`
+    protection_stops: Arc<AtomicUsize>,
+    /// whether `reader_thread` discards samples instead of buffering them
+    paused: Arc<AtomicBool>,
+    /// maximum number of samples kept in `scan_buffer`
+    buffer_capacity: usize,
+    /// what to do when `scan_buffer` is full
+    overflow_policy: OverflowPolicy,
+    /// time the reader thread waits for the motor after starting a scan, set by the builder
+    pub(crate) spin_up_delay: Duration,
+    /// tries of an info, health or conf query before giving up, set by the builder
+    pub(crate) request_attempts: usize,
+    /// samples further than this many millimeters are marked invalid, set from the model by `try_get_info`
+    max_distance: Option<u16>,
+    /// mounting offset added to every sample's angle, in degrees
+    angle_calibration: f32,
+    /// consecutive corrupt messages after which the reader realigns byte by byte
+    resync_threshold: usize,
+    /// file the next reader thread copies the raw stream into
+    recording: Option<File>,
+    /// undecoded messages of a raw scan, oldest first
+    raw_buffer: Arc<Mutex<VecDeque<[u8; 5]>>>,
+    /// notified by `reader_thread` when messages are pushed to `raw_buffer` or the scan ends
+    raw_ready: Arc<Condvar>,
+    /// whether the next reader thread fills `raw_buffer` instead of `scan_buffer`
+    raw: bool,
+    /// the last scan started buffered undecoded messages
+    raw_scan: bool,
+    /// configuration used to reopen `transport`, if it came from a builder
+    config: Option<LidarBuilder>,
 }
 
 impl Lidar {
     /// initializes a serial connection to the lidar on the given port.
-    pub fn init(port: String) -> Result<Lidar, serialport::Error> {
-        serialport::new(&port, S1_BAUD as u32)
-            .timeout(Duration::from_millis(1000))
-            .open()
-            .map(|transport| Lidar {
-                nuke: Arc::new(AtomicBool::new(false)),
-                transport,
-                thread_handle: None,
-            })
+    pub fn init(port: String) -> Result<Lidar, RxError> {
+        LidarBuilder::new().port(port).open()
+    }
+
+    /// initializes a serial connection to the lidar on the given port at the given baud rate.
+    ///
+    /// A-series models use 115200, the S-series uses 256000.
+    pub fn init_with_baud(port: String, baud: u32) -> Result<Lidar, RxError> {
+        LidarBuilder::new().port(port).baud(baud).open()
+    }
+
+    /// Wraps an opened serial connection, see [`LidarBuilder`]
+    pub(crate) fn from_transport(transport: Box<dyn SerialPort>, buffer_capacity: usize, config: Option<LidarBuilder>) -> Lidar {
+        Lidar {
+            state: Arc::new(AtomicU8::new(LidarState::Idle.into())),
+            transport,
+            thread_handle: None,
+            scan_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(buffer_capacity))),
+            sample_ready: Arc::new(Condvar::new()),
+            partial_scan: Mutex::new(Vec::new()),
+            stats: Arc::new(Mutex::new(ScanStats::default())),
+            protection_stops: Arc::new(AtomicUsize::new(0)),
+            paused: Arc::new(AtomicBool::new(false)),
+            buffer_capacity,
+            overflow_policy: OverflowPolicy::default(),
+            spin_up_delay: SPIN_UP_DELAY,
+            request_attempts: REQUEST_ATTEMPTS,
+            max_distance: None,
+            angle_calibration: 0.0,
+            resync_threshold: RESYNC_THRESHOLD,
+            recording: None,
+            raw_buffer: Arc::new(Mutex::new(VecDeque::new())),
+            raw_ready: Arc::new(Condvar::new()),
+            raw: false,
+            raw_scan: false,
+            config,
+        }
+    }
+
+    /// Reopens the serial port, e.g. after the lidar was unplugged.
+    ///
+    /// Any running scan is stopped and the reader thread joined before the connection is replaced.
+    pub fn reconnect(&mut self) -> Result<(), RxError> {
+        let config = self.config.clone().ok_or(RxError::OperationNotSupport)?;
+
+        self.shutdown();
+        self.transport = config.open_transport()?;
+        swap_state(&self.state, LidarState::Idle);
+        self.scan_buffer.lock().unwrap().clear();
+        Ok(())
+    }
+
+    /// Returns to `Idle`, keeping a protection stop unless `reset` is set
+    fn leave_scanning(&self, reset: bool) {
+        // only a reset clears a protection stop
+        let _ = self.state.fetch_update(Ordering::AcqRel, Ordering::Acquire, |state| {
+            (reset || LidarState::from(state) != LidarState::ProtectionStop).then_some(LidarState::Idle.into())
+        });
+    }
+
+    /// Stops the lidar and the reader thread without failing, for when the port may be gone
+    fn shutdown(&mut self) {
+        self.leave_scanning(false);
+        let _ = self.transport.write_all(&[0xa5, Stop as u8]);
+        self.join();
+    }
+
+    /// Creates a lidar that replays a recorded byte stream instead of talking to a device
+    ///
+    /// A recording is the raw bytes the lidar sent in response to a scan request, starting
+    /// with the 7 byte response descriptor. Requests are accepted and ignored. Once the
+    /// recording runs out the lidar behaves as if the device stopped responding.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn from_replay(bytes: Vec<u8>) -> Lidar {
+        Lidar::from_channel(crate::testing::MockChannel::new(bytes))
+    }
+
+    /// Creates a lidar talking to an in-memory port, keep a clone of the channel to feed it and inspect the writes
+    #[cfg(any(test, feature = "testing"))]
+    pub fn from_channel(channel: crate::testing::MockChannel) -> Lidar {
+        Lidar::from_transport(Box::new(channel), SCAN_BUFFER_CAPACITY, None)
     }
 
     /// Generates the checksum for a given message
-    fn checksum(payload: &[u8]) -> u8 {
+    ///
+    /// Covers the whole request frame up to the checksum, starting with the `0xa5` sync byte.
+    pub fn checksum(payload: &[u8]) -> u8 {
         payload.iter().fold(0, |acc, x| acc ^ x)
     }
 
+    /// Runs `f` on the underlying serial port, for settings such as DTR or flow control that are not wrapped
+    ///
+    /// Reading from or writing to the port, or changing its baud rate, desyncs the protocol with the lidar.
+    pub fn with_port<R>(&mut self, f: impl FnOnce(&mut dyn SerialPort) -> R) -> R {
+        f(self.transport.as_mut())
+    }
+
+    /// Discards bytes received but not read yet, such as leftovers of a stopped scan
+    pub fn flush_input(&mut self) -> Result<(), RxError> {
+        self.transport.clear(ClearBuffer::Input)?;
+        Ok(())
+    }
+
     /// Performs a request with a single response
+    ///
+    /// Fails with [`RxError::AlreadyScanning`] while a scan runs, the reader thread owns the
+    /// incoming stream and a request would stop the scan.
     fn single_req(&mut self, req: &[u8]) -> Result<Response, RxError> {
-        self.transport.write_all(&req)?;
+        if self.is_scanning() {
+            return Err(RxError::AlreadyScanning);
+        }
+        // stale bytes would be read as the response descriptor
+        self.flush_input()?;
+        self.transport.write_all(req)?;
         // response header
         let mut descriptor_bytes = [0u8; 7];
 
         self.transport.read_exact(&mut descriptor_bytes)?;
 
         if descriptor_bytes[0..2] != [0xa5, 0x5a] {
-            return Err(RxError::Corrupted(descriptor_bytes));
+            return Err(RxError::Corrupted(descriptor_bytes.to_vec()));
         }
 
         let send_mode = (descriptor_bytes[5] & 0b11000000) >> 6;
@@ -70,19 +327,93 @@ impl Lidar {
             data_type,
         };
 
+        // single requests expect a single response
+        if descriptor.send_mode != 0 {
+            return Err(RxError::Corrupted(descriptor_bytes.to_vec()));
+        }
+
         // data
         let mut data = vec![0u8; descriptor.len as usize];
-        self.transport.read_exact(&mut data)?;
+        let mut received = 0;
+        while received < data.len() {
+            match self.transport.read(&mut data[received..]) {
+                Ok(0) => break,
+                Ok(n) => received += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        // the lidar stopped sending before the announced length
+        if received != data.len() {
+            return Err(RxError::TimedOut);
+        }
 
         Ok(Response { descriptor, data })
     }
 
+    /// Performs a request with a single response, retrying up to `attempts` times on corrupt data or a timeout
+    fn single_req_retry(&mut self, req: &[u8], attempts: usize) -> Result<Response, RxError> {
+        let mut failures = 0;
+        loop {
+            match self.single_req(req) {
+                Err(RxError::Corrupted(_) | RxError::TimedOut) if failures + 1 < attempts => {
+                    failures += 1;
+                    // the rest of the failed response is flushed by the next attempt
+                    sleep(RETRY_BACKOFF * failures as u32);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a request with the given opcode and payload, returning the raw response
+    ///
+    /// Requests without a payload are sent without a length byte or checksum, like the
+    /// built-in commands. Only commands that answer with a single response are supported.
+    pub fn send_command(&mut self, opcode: u8, payload: &[u8]) -> Result<Response, RxError> {
+        if payload.is_empty() {
+            return self.single_req(&[0xa5, opcode]);
+        }
+
+        let len = u8::try_from(payload.len()).map_err(|_| {
+            serialport::Error::new(serialport::ErrorKind::InvalidInput, "payload too long")
+        })?;
+        let mut req = vec![0xa5, opcode, len];
+        req.extend_from_slice(payload);
+        req.push(Self::checksum(&req));
+        self.single_req(&req)
+    }
+
     /// stops the lidar
     pub fn stop(&mut self, reset: bool) {
-        self.nuke.store(true, Ordering::Relaxed);
+        self.leave_scanning(reset);
         self.transport
             .write_all(&[0xa5, (if reset { Reset } else { Stop }) as u8])
             .unwrap();
+        let _ = self.flush_input();
+    }
+
+    /// Stops buffering samples while keeping the scan running, so it can be resumed instantly.
+    pub fn pause_scan(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes buffering samples after [`Lidar::pause_scan`].
+    pub fn resume_scan(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Stops a running scan and waits for the reader thread to exit.
+    ///
+    /// Does nothing if no scan is running.
+    pub fn stop_scan(&mut self) {
+        if self.thread_handle.is_none() {
+            return;
+        }
+        self.stop(false);
+        self.join();
     }
 
     /// Resets/reboots the lidar
@@ -90,70 +421,227 @@ impl Lidar {
         self.stop(true);
     }
 
-    /// Sets motor speed
-    ///
-    /// _Unsupported on RPLIDAR S1?_
+    /// Sets the PWM duty cycle of a motor driven by the accessory board, see [`motor_ctrl_support`](Self::motor_ctrl_support)
     ///
-    /// TODO check this
-    fn set_motor_speed(&mut self, speed: u16) {
-        let speed_bytes = speed.to_le_bytes();
-        let mut req = [
-            0xa5,
-            HQMotorSpeedCtrl as u8,
-            0x02,
-            speed_bytes[0],
-            speed_bytes[1],
-            0,
-        ];
+    /// The duty cycle is clamped to [`MOTOR_PWM_MIN`]`..=`[`MOTOR_PWM_MAX`], and the clamped value is returned.
+    pub fn set_motor_speed(&mut self, speed: u16) -> u16 {
+        let speed = speed.clamp(MOTOR_PWM_MIN, MOTOR_PWM_MAX);
+        self.write_motor_command(SetMotorPWM, speed);
+        speed
+    }
+
+    /// Sets the motor speed in revolutions per minute, on models that regulate their own motor
+    pub fn set_motor_rpm(&mut self, rpm: u16) {
+        self.write_motor_command(HQMotorSpeedCtrl, rpm);
+    }
+
+    /// Spins down the motor without resetting the lidar
+    pub fn stop_motor(&mut self) {
+        // units ignore the command they do not support
+        self.write_motor_command(SetMotorPWM, 0);
+        self.write_motor_command(HQMotorSpeedCtrl, 0);
+    }
+
+    /// Sends a motor command with a 16 bit value
+    fn write_motor_command(&mut self, cmd: SlLidarCmd, value: u16) {
+        let value_bytes = value.to_le_bytes();
+        let mut req = [0xa5, cmd as u8, 0x02, value_bytes[0], value_bytes[1], 0];
         req[5] = Self::checksum(&req);
-        self.transport.write_all(&req).unwrap()
+        self.transport.write_all(&req).unwrap();
+    }
+
+    /// Ensures a response carries at least `len` bytes of data
+    fn expect_len(data: Vec<u8>, len: usize) -> Result<Vec<u8>, RxError> {
+        if data.len() < len {
+            return Err(RxError::Corrupted(data));
+        }
+        Ok(data)
     }
 
     /// Retrieves device information
     pub fn get_info(&mut self) -> SlLidarResponseDeviceInfoT {
-        let res = self
-            .single_req(&[0xa5, GetDeviceInfo as u8])
-            .expect("Could not read device info");
-        let data = res.data;
+        self.try_get_info().expect("Could not read device info")
+    }
+
+    /// Retrieves device information, returning an error on a failed or short read
+    pub fn try_get_info(&mut self) -> Result<SlLidarResponseDeviceInfoT, RxError> {
+        let res = self.single_req_retry(&[0xa5, GetDeviceInfo as u8], self.request_attempts)?;
+        let data = Self::expect_len(res.data, 20)?;
 
-        SlLidarResponseDeviceInfoT {
+        let info = SlLidarResponseDeviceInfoT {
             model: data[0],
             firmware_version: ((data[2] as u16) << 8) | data[1] as u16,
             hardware_version: data[3],
             serial_number: data[4..20].try_into().unwrap(),
+        };
+        self.max_distance.get_or_insert(info.model_kind().max_distance_mm());
+        Ok(info)
+    }
+
+    /// Limits the scan buffer to `cap` samples, handling further samples according to `policy`
+    ///
+    /// Takes effect on the next scan.
+    pub fn set_buffer_policy(&mut self, cap: usize, policy: OverflowPolicy) {
+        self.buffer_capacity = cap.max(1);
+        self.overflow_policy = policy;
+    }
+
+    /// Sets how many consecutive corrupt messages the reader tolerates before it realigns
+    /// to the stream byte by byte, takes effect on the next scan
+    pub fn set_resync_threshold(&mut self, threshold: usize) {
+        self.resync_threshold = threshold;
+    }
+
+    /// Marks samples further than `mm` millimeters invalid, takes effect on the next scan
+    ///
+    /// Defaults to the range of the model once device info has been retrieved.
+    pub fn set_max_distance(&mut self, mm: u16) {
+        self.max_distance = Some(mm);
+    }
+
+    /// Adds `offset_deg` to the angle of every sample, modulo 360, takes effect on the next scan
+    ///
+    /// Nulls out the mounting error so 0° points where the robot's forward is.
+    pub fn set_angle_calibration(&mut self, offset_deg: f32) {
+        self.angle_calibration = offset_deg;
+    }
+
+    /// Retrieves device information along with the unique id of the unit
+    ///
+    /// Fails with [`RxError::OperationNotSupport`] if the firmware does not report the id.
+    pub fn get_device_info_extended(&mut self) -> Result<SlLidarResponseDeviceInfoExtendedT, RxError> {
+        let info = self.try_get_info()?;
+        let macaddr = match self.try_get_lidar_conf(LidarMacAddr, None) {
+            Ok(conf) if conf.payload.len() >= 6 => conf.payload[..6].try_into().unwrap(),
+            Ok(_) | Err(RxError::TimedOut) => return Err(RxError::OperationNotSupport),
+            Err(err) => return Err(err),
+        };
+
+        Ok(SlLidarResponseDeviceInfoExtendedT { info, macaddr })
+    }
+
+    /// Queries how the motor speed can be controlled, see [`set_motor_speed`](Self::set_motor_speed)
+    pub fn motor_ctrl_support(&mut self) -> Result<MotorCtrlSupport, RxError> {
+        // time-of-flight models regulate their own motor speed
+        if matches!(self.try_get_info()?.model_kind(), LidarModel::S1 | LidarModel::S2) {
+            return Ok(MotorCtrlSupport::Rpm);
         }
+
+        let flag = match self.send_command(GetAccBoardFlag as u8, &0u32.to_le_bytes()) {
+            Ok(res) => {
+                let data = Self::expect_len(res.data, 4)?;
+                u32::from_le_bytes(data[..4].try_into().unwrap())
+            }
+            // units without an accessory board do not answer
+            Err(RxError::TimedOut) => return Ok(MotorCtrlSupport::None),
+            Err(err) => return Err(err),
+        };
+
+        Ok(if flag & ACC_BOARD_FLAG_MOTOR_CTRL_SUPPORT_MASK != 0 {
+            MotorCtrlSupport::Pwm
+        } else {
+            MotorCtrlSupport::None
+        })
     }
 
     /// Retrieves the lidar's health
     pub fn get_health(&mut self) -> SlLidarResponseDeviceHealthT {
-        let res = self
-            .single_req(&[0xa5, GetDeviceHealth as u8])
-            .expect("Could not read device health");
-        let data = res.data;
+        self.try_get_health().expect("Could not read device health")
+    }
 
-        SlLidarResponseDeviceHealthT {
+    /// Retrieves the lidar's health, returning an error on a failed or short read
+    pub fn try_get_health(&mut self) -> Result<SlLidarResponseDeviceHealthT, RxError> {
+        let res = self.single_req_retry(&[0xa5, GetDeviceHealth as u8], self.request_attempts)?;
+        let data = Self::expect_len(res.data, 3)?;
+
+        Ok(SlLidarResponseDeviceHealthT {
             status: data[0],
             error_code: ((data[2] as u16) << 8) | data[1] as u16,
+        })
+    }
+
+    /// Retrieves the lidar's health, entering [`LidarState::ProtectionStop`] if it reports an error
+    ///
+    /// Health can only be polled between scans, while scanning this fails with [`RxError::AlreadyScanning`].
+    pub fn poll_health(&mut self) -> Result<SlLidarResponseDeviceHealthT, RxError> {
+        let health = self.try_get_health()?;
+        if health.needs_reset() && swap_state(&self.state, LidarState::ProtectionStop) != LidarState::ProtectionStop {
+            self.protection_stops.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(health)
+    }
+
+    /// Polls the lidar's health until it has stayed healthy for 200 ms
+    ///
+    /// The health status does not reflect the motor speed, so this only confirms the lidar answers
+    /// without faults, after a fixed settling time. It does not detect the motor reaching its target.
+    ///
+    /// Fails with `TimedOut` if that does not happen within `timeout`, or with `ProtectionStop`
+    /// if the lidar reports an error. Cannot be used while scanning.
+    pub fn wait_for_motor(&mut self, timeout: Duration) -> Result<(), RxError> {
+        if self.is_scanning() {
+            return Err(RxError::AlreadyScanning);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut healthy_since = None;
+        loop {
+            let now = Instant::now();
+            match self.poll_health() {
+                Ok(health) if health.needs_reset() => return Err(RxError::ProtectionStop),
+                Ok(health) if health.is_healthy() => {
+                    if now - *healthy_since.get_or_insert(now) >= MOTOR_SETTLE {
+                        return Ok(());
+                    }
+                }
+                // a spinning up lidar may report warnings or not answer in time
+                Ok(_) | Err(RxError::TimedOut) => healthy_since = None,
+                Err(err) => return Err(err),
+            }
+
+            if now >= deadline {
+                return Err(RxError::TimedOut);
+            }
+            sleep(Duration::from_millis(50));
         }
     }
 
     pub fn get_health_str(&mut self) -> &'static str {
-        ["healthy", "warning", "error"]
-            .get(self.get_health().status as usize)
-            .unwrap()
+        match self.get_health().health_status() {
+            HealthStatus::Ok => "healthy",
+            HealthStatus::Warning => "warning",
+            HealthStatus::Error => "error",
+        }
+    }
+
+    /// Retrieves device info, health and sample rate in one go, e.g. for a startup check
+    pub fn self_test(&mut self) -> Result<Diagnostics, RxError> {
+        let info = self.try_get_info()?;
+        let health = self.try_get_health()?;
+        let sample_rate = self.try_get_sample_rate()?;
+
+        Ok(Diagnostics {
+            ok: health.is_healthy(),
+            info,
+            health,
+            sample_rate,
+        })
     }
 
     /// Returns the sampling rate of the lidar
     pub fn get_sample_rate(&mut self) -> SlLidarResponseSampleRateT {
-        let res = self
-            .single_req(&[0xa5, GetSampleRate as u8])
-            .expect("Could not read sample rate");
-        let data = res.data;
+        self.try_get_sample_rate().expect("Could not read sample rate")
+    }
+
+    /// Returns the sampling rate of the lidar, returning an error on a failed or short read
+    pub fn try_get_sample_rate(&mut self) -> Result<SlLidarResponseSampleRateT, RxError> {
+        let res = self.single_req_retry(&[0xa5, GetSampleRate as u8], self.request_attempts)?;
+        let data = Self::expect_len(res.data, 4)?;
 
-        SlLidarResponseSampleRateT {
+        Ok(SlLidarResponseSampleRateT {
             std_sample_duration_us: ((data[1] as u16) << 8) | data[0] as u16,
             express_sample_duration_us: ((data[3] as u16) << 8) | data[2] as u16,
-        }
+        })
     }
 
     /// Queries the lidar for specific configuration settings
@@ -162,6 +650,16 @@ impl Lidar {
         entry: ScanModeConfEntry,
         payload: Option<u16>,
     ) -> SlLidarResponseGetLidarConf {
+        self.try_get_lidar_conf(entry, payload)
+            .expect("Could not read lidar conf")
+    }
+
+    /// Queries the lidar for specific configuration settings, returning an error on a failed or short read
+    pub fn try_get_lidar_conf(
+        &mut self,
+        entry: ScanModeConfEntry,
+        payload: Option<u16>,
+    ) -> Result<SlLidarResponseGetLidarConf, RxError> {
         let mut req = [0u8; 12];
 
         req[0] = 0xa5;
@@ -169,124 +667,586 @@ impl Lidar {
         req[3..7].copy_from_slice((entry as u32).to_le_bytes().as_ref());
 
         match entry {
-            Count | Typical => {
+            Count | Typical | DesiredRotFreq | LidarMacAddr => {
                 req[2] = 4;
                 req[7] = Self::checksum(&req[..7]);
             }
             _ => {
+                let mode_id = payload.ok_or_else(|| {
+                    serialport::Error::new(serialport::ErrorKind::InvalidInput, "no scan mode given")
+                })?;
                 req[2] = 8;
-                req[7..9].copy_from_slice(payload.unwrap().to_le_bytes().as_ref());
+                req[7..9].copy_from_slice(mode_id.to_le_bytes().as_ref());
                 req[11] = Self::checksum(&req[..11]);
             }
         }
 
-        let res = self
-            .single_req(
-                &req[..(match entry {
-                    Count | Typical => 8,
-                    _ => 12,
-                })],
-            )
-            .expect("Could not read lidar conf");
-        let data = res.data;
+        let len = match entry {
+            Count | Typical | DesiredRotFreq | LidarMacAddr => 8,
+            _ => 12,
+        };
+        let res = self.single_req_retry(&req[..len], self.request_attempts)?;
+        let data = Self::expect_len(res.data, 4)?;
+
+        // an answer for another entry means the response is out of sync
+        let conf_type = u32::from_le_bytes(data[..4].try_into().unwrap());
+        if conf_type != entry as u32 {
+            return Err(RxError::Corrupted(data));
+        }
 
-        SlLidarResponseGetLidarConf {
-            conf_type: u32::from_le_bytes(data[..4].try_into().unwrap()),
+        Ok(SlLidarResponseGetLidarConf {
+            conf_type,
             payload: data[4..].to_owned(),
+        })
+    }
+
+    /// Reads a little-endian `u16` configuration value
+    fn conf_u16(conf: SlLidarResponseGetLidarConf) -> Result<u16, RxError> {
+        let payload = Self::expect_len(conf.payload, 2)?;
+        Ok(u16::from_le_bytes([payload[0], payload[1]]))
+    }
+
+    /// Reads a little-endian `u32` configuration value
+    fn conf_u32(conf: SlLidarResponseGetLidarConf) -> Result<u32, RxError> {
+        let payload = Self::expect_len(conf.payload, 4)?;
+        Ok(u32::from_le_bytes(payload[..4].try_into().unwrap()))
+    }
+
+    /// Retrieves the id of the scan mode recommended by the manufacturer
+    pub fn typical_scan_mode(&mut self) -> Result<u16, RxError> {
+        Self::conf_u16(self.try_get_lidar_conf(Typical, None)?)
+    }
+
+    /// Retrieves the rotation frequency in Hz the lidar's scan modes are calibrated for
+    pub fn desired_rotation_freq(&mut self) -> Result<f32, RxError> {
+        // rpm followed by the matching pwm reference
+        let payload = Self::expect_len(self.try_get_lidar_conf(DesiredRotFreq, None)?.payload, 2)?;
+        let rpm = u16::from_le_bytes([payload[0], payload[1]]);
+        Ok(rpm as f32 / 60.0)
+    }
+
+    /// Retrieves the answer type of the packets produced by the given scan mode, see [`SlLidarAnsType`]
+    pub fn scan_mode_answer_type(&mut self, mode_id: u16) -> Result<u8, RxError> {
+        let payload = Self::expect_len(self.try_get_lidar_conf(AnsType, Some(mode_id))?.payload, 1)?;
+        Ok(payload[0])
+    }
+
+    /// Retrieves the name of the given scan mode, e.g. "Standard" or "Boost"
+    pub fn scan_mode_name(&mut self, mode_id: u16) -> Result<String, RxError> {
+        let payload = self.try_get_lidar_conf(Name, Some(mode_id))?.payload;
+        // nul terminated, possibly followed by padding
+        let len = payload.iter().position(|&b| b == 0).unwrap_or(payload.len());
+        Ok(String::from_utf8_lossy(&payload[..len]).into_owned())
+    }
+
+    /// Retrieves the time the given scan mode takes per sample in microseconds
+    pub fn scan_mode_us_per_sample(&mut self, mode_id: u16) -> Result<f32, RxError> {
+        // Q8 fixed point
+        let us_per_sample = Self::conf_u32(self.try_get_lidar_conf(UsPerSample, Some(mode_id))?)?;
+        Ok(us_per_sample as f32 / 256.0)
+    }
+
+    /// Retrieves the maximum range of the given scan mode in meters
+    pub fn scan_mode_max_distance(&mut self, mode_id: u16) -> Result<f32, RxError> {
+        // Q8 fixed point
+        let max_distance = Self::conf_u32(self.try_get_lidar_conf(MaxDistance, Some(mode_id))?)?;
+        Ok(max_distance as f32 / 256.0)
+    }
+
+    /// Retrieves descriptions of all scan modes supported by the lidar
+    pub fn get_scan_modes(&mut self) -> Result<Vec<ScanMode>, RxError> {
+        let count = Self::conf_u16(self.try_get_lidar_conf(Count, None)?)?;
+
+        (0..count)
+            .map(|id| {
+                let name = self.scan_mode_name(id)?;
+                let us_per_sample = self.scan_mode_us_per_sample(id)?;
+                let max_distance = self.scan_mode_max_distance(id)?;
+                let answer_type = self.scan_mode_answer_type(id)?;
+
+                Ok(ScanMode {
+                    id,
+                    name,
+                    us_per_sample,
+                    max_distance,
+                    answer_type,
+                })
+            })
+            .collect()
+    }
+
+    /// Retrieves the id of the scan mode with the longest range
+    ///
+    /// Fails with [`RxError::OperationNotSupport`] if the lidar reports no scan modes.
+    pub fn best_range_scan_mode(&mut self) -> Result<u16, RxError> {
+        self.get_scan_modes()?
+            .into_iter()
+            .max_by(|a, b| a.max_distance.total_cmp(&b.max_distance))
+            .map(|mode| mode.id)
+            .ok_or(RxError::OperationNotSupport)
+    }
+
+    /// Pops the oldest buffered sample, if any, without blocking.
+    pub fn get_sample(&self) -> Option<Sample> {
+        self.scan_buffer.lock().unwrap().pop_front()
+    }
+
+    /// Pops the oldest buffered sample, waiting up to `timeout` for one to arrive.
+    ///
+    /// Returns `None` if the timeout elapses or the scan stops first.
+    pub fn get_sample_timeout(&self, timeout: Duration) -> Option<Sample> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let scanning = self.is_scanning();
+            if let Some(sample) = self.get_sample() {
+                return Some(sample);
+            }
+            if !scanning || Instant::now() >= deadline {
+                return None;
+            }
+            self.wait_for_samples(SAMPLE_WAIT.min(deadline - Instant::now()));
         }
     }
 
+    /// Blocks until samples are buffered, the scan ends, or `timeout` elapses
+    fn wait_for_samples(&self, timeout: Duration) {
+        Self::wait_on(&self.scan_buffer, &self.sample_ready, timeout);
+    }
+
+    /// Waits on `ready` unless `buffer` already holds samples
+    fn wait_on<T>(buffer: &Mutex<VecDeque<T>>, ready: &Condvar, timeout: Duration) {
+        let buffer = buffer.lock().unwrap();
+        if buffer.is_empty() {
+            drop(ready.wait_timeout(buffer, timeout).unwrap());
+        }
+    }
+
+    /// Number of times the lidar has entered [`LidarState::ProtectionStop`], either because a scan
+    /// stalled or because [`Lidar::poll_health`] found an error
+    pub fn protection_stops_seen(&self) -> usize {
+        self.protection_stops.load(Ordering::Relaxed)
+    }
+
+    /// Number of corrupt messages discarded since the current scan started
+    pub fn scan_error_count(&self) -> usize {
+        self.stats.lock().unwrap().samples_dropped
+    }
+
+    /// Statistics of the current scan
+    pub fn stats(&self) -> ScanStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Rotation rate of the current scan measured from revolution boundaries
+    ///
+    /// Returns `None` until two revolution boundaries have been received.
+    pub fn scan_frequency_hz(&self) -> Option<f32> {
+        let period = self.stats.lock().unwrap().revolution_period?;
+        Some(1.0 / period.as_secs_f32())
+    }
+
+    /// Current state of the lidar
+    ///
+    /// `Scanning` is only reported while the reader thread is receiving data.
+    pub fn state(&self) -> LidarState {
+        load_state(&self.state)
+    }
+
+    /// Fails if the lidar is in a state that does not allow starting a scan
+    fn ensure_can_scan(&mut self) -> Result<(), RxError> {
+        match self.state() {
+            LidarState::Scanning => Err(RxError::AlreadyScanning),
+            LidarState::ProtectionStop => Err(RxError::ProtectionStop),
+            LidarState::Idle => {
+                // a stopped reader thread may still be winding down
+                self.join();
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether a reader thread is currently receiving scan data
+    fn is_scanning(&self) -> bool {
+        load_state(&self.state) == LidarState::Scanning
+            && self.thread_handle.as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Returns an iterator that blocks on each sample as it arrives.
+    ///
+    /// The iterator ends once the scan has stopped and the buffer is drained.
+    pub fn samples(&self) -> Samples<'_> {
+        Samples { lidar: self }
+    }
+
+    /// Waits until a complete revolution has been received and returns it.
+    ///
+    /// Samples preceding the first revolution boundary are discarded. If the scan
+    /// stops before the revolution completes, the incomplete revolution is returned.
+    pub fn next_scan(&self) -> Vec<Sample> {
+        let mut scan: Vec<Sample> = Vec::new();
+
+        loop {
+            let scanning = self.is_scanning();
+            if self.collect_scan(&mut scan) || !scanning {
+                return scan;
+            }
+            self.wait_for_samples(SAMPLE_WAIT);
+        }
+    }
+
+    /// Waits up to `timeout` for a complete revolution and returns it.
+    ///
+    /// Fails with `TimedOut` if no complete revolution arrives in time or the scan stops first,
+    /// the partial revolution received so far is kept and completed by the next call.
+    pub fn get_full_scan_timeout(&self, timeout: Duration) -> Result<Vec<Sample>, RxError> {
+        let deadline = Instant::now() + timeout;
+        let mut scan = std::mem::take(&mut *self.partial_scan.lock().unwrap());
+
+        loop {
+            let scanning = self.is_scanning();
+            if self.collect_scan(&mut scan) {
+                return Ok(scan);
+            }
+            if !scanning || Instant::now() >= deadline {
+                *self.partial_scan.lock().unwrap() = scan;
+                return Err(RxError::TimedOut);
+            }
+            self.wait_for_samples(SAMPLE_WAIT.min(deadline - Instant::now()));
+        }
+    }
+
+    /// Moves buffered samples of the current revolution into `scan`.
+    ///
+    /// Samples preceding the first revolution boundary are discarded.
+    /// Returns true once the next revolution has started, i.e. `scan` is complete.
+    fn collect_scan(&self, scan: &mut Vec<Sample>) -> bool {
+        Self::collect_revolution(&mut self.scan_buffer.lock().unwrap(), scan, |sample| sample.start)
+    }
+
+    /// Moves the messages of the current revolution from `buffer` into `scan`, see [`Lidar::collect_scan`]
+    fn collect_revolution<T>(buffer: &mut VecDeque<T>, scan: &mut Vec<T>, is_start: impl Fn(&T) -> bool) -> bool {
+        while let Some(message) = buffer.front() {
+            // the next revolution starts here, leave it for the next call
+            if is_start(message) && !scan.is_empty() {
+                return true;
+            }
+
+            let message = buffer.pop_front().unwrap();
+            if scan.is_empty() && !is_start(&message) {
+                continue;
+            }
+            scan.push(message);
+        }
+        false
+    }
+
+    /// Consumes the lidar, returning an iterator over the revolutions of the running scan.
+    ///
+    /// The iterator ends once the scan has stopped, dropping it stops the scan.
+    pub fn into_scans(self) -> Scans {
+        Scans { lidar: self }
+    }
+
+    /// Waits until `n` samples have been received and removes them from the buffer.
+    ///
+    /// Fewer are returned if the scan stops first.
+    pub fn get_n_samples(&self, n: u32) -> Vec<Sample> {
+        let n = n as usize;
+        let mut samples = Vec::with_capacity(n);
+
+        loop {
+            let scanning = self.is_scanning();
+            self.take_samples(&mut samples, n);
+            if samples.len() == n || !scanning {
+                return samples;
+            }
+            self.wait_for_samples(SAMPLE_WAIT);
+        }
+    }
+
+    /// Waits until `n` samples have been received and passes them to `f` without copying.
+    ///
+    /// The samples are left in the buffer, and fewer are passed if the scan stops first.
+    /// The buffer stays locked while `f` runs, stalling the reader thread.
+    pub fn with_samples<R>(&self, n: u32, f: impl FnOnce(&[Sample]) -> R) -> R {
+        let n = n as usize;
+        let mut buffer = self.scan_buffer.lock().unwrap();
+        while buffer.len() < n && self.is_scanning() {
+            buffer = self.sample_ready.wait_timeout(buffer, SAMPLE_WAIT).unwrap().0;
+        }
+
+        let len = n.min(buffer.len());
+        f(&buffer.make_contiguous()[..len])
+    }
+
+    /// Waits up to `timeout` for `n` samples and removes them from the buffer.
+    ///
+    /// Returns fewer than `n` samples if the timeout elapses or the scan stops first.
+    pub fn get_n_samples_timeout(&self, n: u32, timeout: Duration) -> Vec<Sample> {
+        let deadline = Instant::now() + timeout;
+        let n = n as usize;
+        let mut samples = Vec::with_capacity(n);
+
+        loop {
+            let scanning = self.is_scanning();
+            self.take_samples(&mut samples, n);
+            if samples.len() == n || !scanning || Instant::now() >= deadline {
+                return samples;
+            }
+            self.wait_for_samples(SAMPLE_WAIT.min(deadline - Instant::now()));
+        }
+    }
+
+    /// Moves buffered samples into `samples` until it holds `n`
+    fn take_samples(&self, samples: &mut Vec<Sample>, n: usize) {
+        let mut buffer = self.scan_buffer.lock().unwrap();
+        let available = (n - samples.len()).min(buffer.len());
+        samples.extend(buffer.drain(..available));
+    }
+
+    /// Waits until `n` valid samples have been received, discarding invalid ones.
+    ///
+    /// Fewer are returned if the scan stops first.
+    pub fn get_n_valid_samples(&self, n: u32) -> Vec<Sample> {
+        let mut samples = Vec::with_capacity(n as usize);
+        while samples.len() < n as usize {
+            let missing = n - samples.len() as u32;
+            let received = self.get_n_samples(missing);
+            let stopped = received.len() < missing as usize;
+            samples.extend(received.into_iter().filter(Sample::is_valid));
+            if stopped {
+                break;
+            }
+        }
+        samples
+    }
+
     /// Waits for the reader thread to exit.
+    ///
+    /// A reader that panicked on a failed port is joined without propagating the panic.
     pub fn join(&mut self) {
         if let Some(handle) = self.thread_handle.take() {
-            handle.join().unwrap();
+            // the reader panics on a dead port, and has already reported why
+            if handle.join().is_err() {
+                log::warn!("Lidar reader thread panicked");
+            }
         }
     }
 
     /// Requests transmission of laser data from the lidar
-    pub fn start_scan(&mut self) -> Result<Receiver<Sample>, serialport::Error> {
+    pub fn start_scan(&mut self) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
+
         // signal lidar to begin a scan
         self.transport.write_all(&[0xa5, Scan as u8])?;
 
-        let nuke = Arc::clone(&self.nuke);
-        let (tx, rx) = mpsc::channel();
-        let transport = self
-            .transport
-            .try_clone()
-            .expect("Serial port channels can be cloned.");
+        self.spawn_reader(Measurement);
+        Ok(())
+    }
 
-        // start reader thread
-        self.thread_handle = Some(thread::spawn(move || {
-            Self::reader_thread(tx, transport, nuke);
-        }));
+    /// Spins up the motor, waits for it to settle, then starts a standard scan
+    ///
+    /// Models regulating their own motor spin at `rpm`. Motors driven by the accessory board
+    /// run at [`MOTOR_PWM_DEFAULT`] instead, see [`motor_ctrl_support`](Self::motor_ctrl_support).
+    pub fn start_scan_managed(&mut self, rpm: u16) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
+
+        match self.motor_ctrl_support()? {
+            MotorCtrlSupport::Rpm => self.set_motor_rpm(rpm),
+            MotorCtrlSupport::Pwm => {
+                self.set_motor_speed(MOTOR_PWM_DEFAULT);
+            }
+            MotorCtrlSupport::None => {}
+        }
+        self.wait_for_motor(MOTOR_SPIN_UP)?;
 
-        Ok(rx)
+        // the motor is already up to speed
+        let spin_up_delay = std::mem::replace(&mut self.spin_up_delay, Duration::ZERO);
+        let result = self.start_scan();
+        self.spin_up_delay = spin_up_delay;
+        result
     }
 
-    /// Thread that receives scan data
-    fn reader_thread(tx: Sender<Sample>, mut transport: Box<dyn SerialPort>, nuke: Arc<AtomicBool>) {
-        let mut seeking = true;
-        let mut descriptor = [0u8; 7];
+    /// Stops a scan started with [`Lidar::start_scan_managed`], then spins down the motor
+    pub fn stop_scan_managed(&mut self) {
+        self.stop_scan();
+        self.stop_motor();
+    }
 
-        transport
-            .read_exact(&mut descriptor)
-            .expect("missing descriptor");
+    /// Starts a standard scan that buffers the messages received from the lidar without decoding them.
+    ///
+    /// The messages are available through [`Lidar::get_raw_frame`] rather than as samples.
+    pub fn start_scan_raw(&mut self) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
 
-        if descriptor != [0xa5, 0x5a, 0x05, 0x00, 0x00, 0x40, 0x81] {
-            eprintln!("Unable to read lidar stream (malformed descriptor)");
-            return;
-        }
+        self.raw = true;
+        let result = self.start_scan();
+        self.raw = false;
+        result
+    }
 
-        // give the lidar time to spin up
-        sleep(Duration::from_millis(1000));
+    /// Pops the oldest undecoded message of a raw scan, if any, without blocking.
+    pub fn get_raw_frame(&self) -> Option<[u8; 5]> {
+        self.raw_buffer.lock().unwrap().pop_front()
+    }
+
+    /// Waits until a complete revolution of a raw scan has been received and returns its bytes.
+    ///
+    /// Messages preceding the first revolution boundary are discarded. If the scan stops
+    /// before the revolution completes, the bytes of the incomplete revolution are returned.
+    /// Returns nothing right away if the last scan was not started with [`Lidar::start_scan_raw`].
+    pub fn read_revolution_bytes(&self) -> Vec<u8> {
+        let mut frames = Vec::new();
+        if !self.raw_scan {
+            return Vec::new();
+        }
 
         loop {
-            const BATCH: usize = S1_BAUD / 500;
-            let mut data = [0u8; 5 * BATCH];
-
-            if let Err(err) = transport.read_exact(&mut data) {
-                if nuke.load(Ordering::Relaxed) {
-                    nuke.store(false, Ordering::Relaxed);
-                    println!("Scan stopped.");
-                    return;
-                }
-                panic!("Unable to read lidar stream (malformed data): {}", err);
+            let scanning = self.is_scanning();
+            let complete = Self::collect_revolution(&mut self.raw_buffer.lock().unwrap(), &mut frames, |frame| {
+                frame[0] & 0b11 == 0b01
+            });
+            if complete || !scanning {
+                return frames.concat();
             }
+            Self::wait_on(&self.raw_buffer, &self.raw_ready, SAMPLE_WAIT);
+        }
+    }
 
-            for i in 0..BATCH {
-                let slice = &data[i * 5..i * 5 + 5];
+    /// Starts a standard scan, copying the raw stream received from the lidar into a file.
+    ///
+    /// The recording can be replayed with `Lidar::from_replay`, and ends when the scan stops.
+    pub fn start_scan_recording(&mut self, path: &Path) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
 
-                // checks
-                let s = slice[0] & 0b11;
-                if s == 0b11 || s == 0b00 || slice[1] & 0b01 != 1 {
-                    eprintln!("parity failed: {:x?}", slice);
-                    continue;
-                }
+        self.recording = Some(File::create(path)?);
+        let result = self.start_scan();
+        self.recording = None;
+        result
+    }
 
-                let sample = Sample {
-                    start: (slice[0] & 1) != 0,
-                    intensity: slice[0] >> 2,
-                    angle: ((slice[2] as u16) << 1) | (slice[1] as u16 >> 7),
-                    distance: (((slice[4] as u16) << 8) | slice[3] as u16) / 4,
-                };
+    /// Starts a scan and streams its samples as they arrive.
+    ///
+    /// Must be called from within a tokio runtime. Dropping the stream stops the
+    /// forwarding of samples, but not the scan itself.
+    #[cfg(feature = "async")]
+    pub async fn start_scan_async(&mut self) -> Result<impl tokio_stream::Stream<Item = Sample>, RxError> {
+        self.start_scan()?;
 
-                if seeking && !sample.start {
-                    continue;
-                }
+        let buffer = Arc::clone(&self.scan_buffer);
+        let ready = Arc::clone(&self.sample_ready);
+        let state = Arc::clone(&self.state);
+        let (tx, rx) = tokio::sync::mpsc::channel(self.buffer_capacity);
 
-                seeking = false;
-                tx.send(sample).unwrap();
+        // forward samples from the reader thread
+        tokio::task::spawn_blocking(move || loop {
+            let scanning = load_state(&state) == LidarState::Scanning;
+            let sample = buffer.lock().unwrap().pop_front();
+            match sample {
+                Some(sample) => {
+                    if tx.blocking_send(sample).is_err() {
+                        // stream dropped
+                        return;
+                    }
+                }
+                None if !scanning => return,
+                None => Self::wait_on(&buffer, &ready, SAMPLE_WAIT),
             }
-        }
+        });
+
+        Ok(tokio_stream::wrappers::ReceiverStream::new(rx))
+    }
+
+    /// Starts a scan that is stopped when the returned session is dropped
+    pub fn scan_session(&mut self) -> Result<ScanSession<'_>, RxError> {
+        self.start_scan()?;
+        Ok(ScanSession { lidar: self })
+    }
+
+    /// Requests transmission of laser data from the lidar, regardless of whether the motor is up to speed
+    pub fn force_scan(&mut self) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
+
+        // signal lidar to begin a scan
+        self.transport.write_all(&[0xa5, ForceScan as u8])?;
+
+        self.spawn_reader(Measurement);
+        Ok(())
+    }
+
+    /// Requests transmission of express (capsuled) laser data from the lidar
+    pub fn start_scan_express(&mut self) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
+
+        // signal lidar to begin an express scan in legacy mode
+        self.transport.write_all(&[0xa5, ExpressScan as u8, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x22])?;
+
+        self.spawn_reader(MeasurementCapsuled);
+        Ok(())
     }
 
     /// Requests transmission of laser data from the lidar
-    pub fn start_scan_dense(&mut self) -> Result<Receiver<Sample>, serialport::Error> {
+    pub fn start_scan_dense(&mut self) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
+
         // signal lidar to begin a scan
         self.transport.write_all(&[0xa5, ExpressScan as u8, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x22])?;
 
-        let nuke = Arc::clone(&self.nuke);
-        let (tx, rx) = mpsc::channel();
+        self.spawn_reader(MeasurementDenseCapsuled);
+        Ok(())
+    }
+
+    /// Starts a scan in the given scan mode, see [`Lidar::get_scan_modes`]
+    pub fn start_scan_mode(&mut self, mode_id: u16) -> Result<(), RxError> {
+        self.ensure_can_scan()?;
+
+        let answer_type = self.scan_mode_answer_type(mode_id)?;
+        let ans_type = SlLidarAnsType::try_from(answer_type)
+            .ok()
+            .filter(|&ans_type| message_size(ans_type).is_some())
+            .ok_or(RxError::OperationNotSupport)?;
+
+        if ans_type == Measurement {
+            // the standard mode uses the legacy scan command
+            self.transport.write_all(&[0xa5, Scan as u8])?;
+        } else {
+            let mut req = [0xa5, ExpressScan as u8, 0x05, mode_id as u8, 0x00, 0x00, 0x00, 0x00, 0x00];
+            req[8] = Self::checksum(&req[..8]);
+            self.transport.write_all(&req)?;
+        }
+
+        self.spawn_reader(ans_type);
+        Ok(())
+    }
+
+    /// Starts a reader thread decoding answers of the given type
+    fn spawn_reader(&mut self, ans_type: SlLidarAnsType) {
+        swap_state(&self.state, LidarState::Scanning);
+        self.scan_buffer.lock().unwrap().clear();
+        self.partial_scan.lock().unwrap().clear();
+        self.raw_buffer.lock().unwrap().clear();
+        self.paused.store(false, Ordering::Relaxed);
+        self.raw_scan = std::mem::take(&mut self.raw);
+        *self.stats.lock().unwrap() = ScanStats::default();
+        let state = Arc::clone(&self.state);
+        let buffer = Arc::clone(&self.scan_buffer);
+        let ready = Arc::clone(&self.sample_ready);
+        let stats = Arc::clone(&self.stats);
+        let stops = Arc::clone(&self.protection_stops);
+        let raw_ready = Arc::clone(&self.raw_ready);
+        let config = ReaderConfig {
+            ans_type,
+            capacity: self.buffer_capacity,
+            policy: self.overflow_policy,
+            spin_up_delay: self.spin_up_delay,
+            max_distance: self.max_distance.unwrap_or(u16::MAX),
+            angle_offset_q6: (self.angle_calibration.rem_euclid(360.0) * 64.0).round() as u16 % (360 << 6),
+            resync_threshold: self.resync_threshold,
+            recording: self.recording.take(),
+            raw_buffer: self.raw_scan.then(|| Arc::clone(&self.raw_buffer)),
+            raw_ready: Arc::clone(&self.raw_ready),
+            paused: Arc::clone(&self.paused),
+        };
         let transport = self
             .transport
             .try_clone()
@@ -294,73 +1254,450 @@ impl Lidar {
 
         // start reader thread
         self.thread_handle = Some(thread::spawn(move || {
-            Self::reader_thread_dense(tx, transport, nuke);
+            Self::reader_thread(buffer, &ready, stats, transport, state, &stops, config);
+            // wake consumers waiting on a scan that has ended
+            ready.notify_all();
+            raw_ready.notify_all();
         }));
+    }
+
+    /// Flags a stalled scan, unless it was stopped in the meantime
+    fn protection_stop(state: &AtomicU8, stops: &AtomicUsize) {
+        if transition_state(state, LidarState::Scanning, LidarState::ProtectionStop) {
+            stops.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns to `Idle` when the reader thread gives up on a scan
+    fn scan_ended(state: &AtomicU8) {
+        transition_state(state, LidarState::Scanning, LidarState::Idle);
+    }
 
-        Ok(rx)
+    /// Accounts for a batch of `received` messages, `starts` of which begin a revolution
+    ///
+    /// While `seeking`, the first start only opens a revolution.
+    fn update_stats(stats: &Mutex<ScanStats>, received: usize, dropped: usize, starts: usize, seeking: bool) {
+        let now = Instant::now();
+        let mut stats = stats.lock().unwrap();
+        stats.samples_dropped += dropped;
+        stats.last_update = Some(now);
+        stats.samples_total += received;
+        stats.revolutions += starts.saturating_sub(seeking as usize);
+        if starts > 0 {
+            if let Some(last_start) = stats.last_revolution_start {
+                stats.revolution_period = Some((now - last_start) / starts as u32);
+            }
+            stats.last_revolution_start = Some(now);
+        }
     }
 
-    fn validate_dense(msg: &[u8]) -> bool {
-        msg[0] >> 4 == 0xa && msg[1] >> 4 == 0x5
-            && Self::checksum(&msg[2..]) == (msg[1] << 4) | (msg[0] & 0b1111)
+    /// Copies received bytes into the recording, abandoning it if the write fails
+    fn record(recording: &mut Option<File>, bytes: &[u8]) {
+        if let Some(file) = recording {
+            if let Err(err) = file.write_all(bytes) {
+                log::warn!("Stopped recording lidar stream: {}", err);
+                *recording = None;
+            }
+        }
     }
 
     /// Thread that receives scan data
-    fn reader_thread_dense(tx: Sender<Sample>, mut transport: Box<dyn SerialPort>, nuke: Arc<AtomicBool>) {
-        let mut seeking = true;
+    fn reader_thread(buffer: Arc<Mutex<VecDeque<Sample>>>, ready: &Condvar, stats: Arc<Mutex<ScanStats>>, mut transport: Box<dyn SerialPort>, state: Arc<AtomicU8>, stops: &AtomicUsize, mut config: ReaderConfig) {
+        let ans_type = config.ans_type;
+        let Ok(mut decoder) = ScanDecoder::new(ans_type) else {
+            Self::scan_ended(&state);
+            log::error!("Unable to read lidar stream (unsupported answer type {:?})", ans_type);
+            return;
+        };
+        decoder.set_resync_threshold(config.resync_threshold);
+        let msg_size = decoder.message_size();
         let mut descriptor = [0u8; 7];
 
-        transport
-            .read_exact(&mut descriptor)
-            .expect("missing descriptor");
+        if let Err(err) = transport.read_exact(&mut descriptor) {
+            if err.kind() == io::ErrorKind::TimedOut {
+                Self::protection_stop(&state, stops);
+                log::error!("Unable to read lidar stream (missing descriptor)");
+                return;
+            }
+            Self::scan_ended(&state);
+            panic!("Unable to read lidar stream (missing descriptor): {}", err);
+        }
 
-        if descriptor != [0xa5, 0x5a, 0x54, 0x00, 0x00, 0x40, 0x85] {
-            eprintln!("Unable to read lidar stream (malformed descriptor)");
+        Self::record(&mut config.recording, &descriptor);
+
+        if descriptor != [0xa5, 0x5a, msg_size as u8, 0x00, 0x00, 0x40, ans_type as u8] {
+            Self::scan_ended(&state);
+            log::error!("Unable to read lidar stream (malformed descriptor)");
             return;
         }
 
         // give the lidar time to spin up
-        sleep(Duration::from_millis(1000));
+        sleep(config.spin_up_delay);
 
-        const MSG_SIZE: usize = 84;
+        let batch = match ans_type {
+            Measurement => S1_BAUD / 500,
+            _ => S1_BAUD / (100 * msg_size),
+        };
+        let mut data = vec![0u8; msg_size * batch];
+        // bytes of a raw scan not yet split into frames
+        let mut pending = Vec::new();
+        let mut raw_seeking = true;
 
         loop {
-            const BATCH: usize = S1_BAUD / (100 * MSG_SIZE);
-            let mut data = [0u8; MSG_SIZE * BATCH];
-
-            if let Err(err) = transport.read_exact(&mut data) {
-                if nuke.load(Ordering::Relaxed) {
-                    nuke.store(false, Ordering::Relaxed);
-                    println!("Scan stopped.");
-                    return;
+            if load_state(&state) != LidarState::Scanning {
+                log::debug!("Scan stopped.");
+                return;
+            }
+
+            // decode whatever has arrived rather than waiting for a full batch
+            let received = match transport.read(&mut data) {
+                Ok(received) => received,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    if load_state(&state) != LidarState::Scanning {
+                        log::debug!("Scan stopped.");
+                        return;
+                    }
+                    if err.kind() == io::ErrorKind::TimedOut {
+                        Self::protection_stop(&state, stops);
+                        log::error!("Unable to read lidar stream (timed out)");
+                        return;
+                    }
+                    Self::scan_ended(&state);
+                    panic!("Unable to read lidar stream (malformed data): {}", err);
                 }
-                panic!("Unable to read lidar stream (malformed data): {}", err);
+            };
+
+            let received_at = Instant::now();
+            Self::record(&mut config.recording, &data[..received]);
+
+            if let Some(raw_buffer) = &config.raw_buffer {
+                pending.extend_from_slice(&data[..received]);
+                let frames = pending.len() / msg_size;
+                let starts = pending[..frames * msg_size]
+                    .chunks_exact(msg_size)
+                    .filter(|frame| frame[0] & 0b11 == 0b01)
+                    .count();
+                {
+                    let mut raw_buffer = raw_buffer.lock().unwrap();
+                    for frame in pending[..frames * msg_size].chunks_exact(msg_size) {
+                        if raw_buffer.len() >= config.capacity {
+                            raw_buffer.pop_front();
+                        }
+                        raw_buffer.push_back(frame.try_into().unwrap());
+                    }
+                }
+                pending.drain(..frames * msg_size);
+
+                Self::update_stats(&stats, frames, 0, starts, raw_seeking);
+                raw_seeking &= starts == 0;
+                config.raw_ready.notify_all();
+                continue;
             }
+            let was_seeking = decoder.is_seeking();
+            let dropped_before = decoder.samples_dropped();
+            let mut samples = decoder.push_bytes_at(&data[..received], received_at);
+            let dropped = decoder.samples_dropped() - dropped_before;
 
-            for i in 0..BATCH {
-                let slice = &data[i * MSG_SIZE..i * MSG_SIZE + MSG_SIZE];
+            let starts = samples.iter().filter(|sample| sample.start).count();
+            Self::update_stats(&stats, samples.len(), dropped, starts, was_seeking);
 
-                // checks
-                if !Self::validate_dense(slice) {
-                    eprintln!("checks failed: {:x?}", slice);
-                    continue;
+            if config.paused.load(Ordering::Relaxed) {
+                samples.clear();
+            }
+
+            let mut queue = buffer.lock().unwrap();
+            for mut sample in samples.drain(..) {
+                // out of range returns are wrap-around artifacts
+                if sample.distance > config.max_distance {
+                    sample.distance = 0;
+                    sample.intensity = 0;
                 }
+                sample.angle_q6 = ((sample.angle_q6 as u32 + config.angle_offset_q6 as u32) % (360 << 6)) as u16;
 
-                let cabins = &slice[4..];
-
-                // let sample = DenseSample {
-                //     start: slice[3] >> 7 != 0,
-                //     angle: (((slice[3] & 0b1111111) as u16) << 1) | (slice[1] as u16 >> 7),
-                //     distance: ,
-                // };
-                //
-                // if seeking && !sample.start {
-                //     continue;
-                // }
-                //
-                // seeking = false;
-                // tx.send(sample).unwrap();
+                if queue.len() >= config.capacity {
+                    match config.policy {
+                        OverflowPolicy::DropOldest => {
+                            queue.pop_front();
+                        }
+                        OverflowPolicy::DropNewest => continue,
+                        OverflowPolicy::Block => {
+                            while queue.len() >= config.capacity {
+                                drop(queue);
+                                if load_state(&state) != LidarState::Scanning {
+                                    log::debug!("Scan stopped.");
+                                    return;
+                                }
+                                ready.notify_all();
+                                sleep(Duration::from_millis(1));
+                                queue = buffer.lock().unwrap();
+                            }
+                        }
+                    }
+                }
+                queue.push_back(sample);
             }
+            drop(queue);
+            ready.notify_all();
+        }
+    }
+}
+
+/// Stops the lidar and waits for the reader thread to exit
+impl Drop for Lidar {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Blocking iterator over the samples of a running scan, see [`Lidar::samples`]
+pub struct Samples<'a> {
+    lidar: &'a Lidar,
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        loop {
+            let scanning = self.lidar.is_scanning();
+            if let Some(sample) = self.lidar.get_sample() {
+                return Some(sample);
+            }
+            if !scanning {
+                return None;
+            }
+            self.lidar.wait_for_samples(SAMPLE_WAIT);
+        }
+    }
+}
+
+/// A running scan that borrows the lidar, see [`Lidar::scan_session`]
+///
+/// Dropping the session stops the scan and waits for the reader thread to exit.
+pub struct ScanSession<'a> {
+    lidar: &'a mut Lidar,
+}
+
+impl ScanSession<'_> {
+    /// Waits for the next sample, or returns `None` once the scan has stopped
+    pub fn next_sample(&self) -> Option<Sample> {
+        self.samples().next()
+    }
+
+    /// Returns an iterator that blocks on each sample as it arrives
+    pub fn samples(&self) -> Samples<'_> {
+        self.lidar.samples()
+    }
+}
+
+/// Stops the scan without panicking, the port may be gone
+impl Drop for ScanSession<'_> {
+    fn drop(&mut self) {
+        self.lidar.shutdown();
+    }
+}
+
+/// Iterator over the revolutions of a running scan that owns the lidar, see [`Lidar::into_scans`]
+///
+/// Dropping the iterator drops the lidar, which stops the scan without panicking.
+pub struct Scans {
+    lidar: Lidar,
+}
+
+impl Iterator for Scans {
+    type Item = Vec<Sample>;
+
+    fn next(&mut self) -> Option<Vec<Sample>> {
+        let scan = self.lidar.next_scan();
+        if scan.is_empty() {
+            return None;
         }
+        Some(scan)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockChannel;
+
+    /// Device info response of an A1M8 with firmware 1.29 and hardware 7
+    fn device_info_response() -> Vec<u8> {
+        let mut bytes = vec![0xa5, 0x5a, 0x14, 0x00, 0x00, 0x00, 0x04];
+        bytes.extend([0x18, 0x1d, 0x01, 0x07]);
+        bytes.extend(0..16);
+        bytes
+    }
+
+    #[test]
+    fn checksum_matches_vendor_frames() {
+        // legacy express scan request
+        assert_eq!(Lidar::checksum(&[0xa5, 0x82, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00]), 0x22);
+        // GetLidarConf for the scan mode count
+        assert_eq!(Lidar::checksum(&[0xa5, 0x84, 0x04, 0x70, 0x00, 0x00, 0x00]), 0x55);
+    }
+
+    #[test]
+    fn motor_speed_is_sent_as_clamped_pwm() {
+        let channel = MockChannel::new(Vec::new());
+        let mut lidar = Lidar::from_channel(channel.clone());
+
+        assert_eq!(lidar.set_motor_speed(2000), MOTOR_PWM_MAX);
+        lidar.set_motor_rpm(600);
+        assert_eq!(
+            channel.writes(),
+            vec![vec![0xa5, 0xf0, 0x02, 0xff, 0x03, 0xab], vec![0xa5, 0xa8, 0x02, 0x58, 0x02, 0x55]]
+        );
+    }
+
+    #[test]
+    fn conf_request_carries_checksum() {
+        let channel = MockChannel::new(vec![0xa5, 0x5a, 0x06, 0x00, 0x00, 0x00, 0x20, 0x70, 0x00, 0x00, 0x00, 0x04, 0x00]);
+        let mut lidar = Lidar::from_channel(channel.clone());
+
+        assert_eq!(Lidar::conf_u16(lidar.try_get_lidar_conf(Count, None).unwrap()).unwrap(), 4);
+        assert_eq!(channel.writes(), vec![vec![0xa5, 0x84, 0x04, 0x70, 0x00, 0x00, 0x00, 0x55]]);
+    }
+
+    #[test]
+    fn get_info_writes_request_and_parses_response() {
+        let channel = MockChannel::new(device_info_response());
+        let mut lidar = Lidar::from_channel(channel.clone());
+
+        let info = lidar.try_get_info().unwrap();
+        assert_eq!(channel.writes(), vec![vec![0xa5, 0x50]]);
+        assert_eq!(info.model, 0x18);
+        assert_eq!(info.firmware_semver(), (1, 29));
+        assert_eq!(info.hardware_version, 7);
+        assert_eq!(info.serial_number, core::array::from_fn(|i| i as u8));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn init_with_baud_opens_port_at_rate() {
+        let (_device, port) = serialport::TTYPort::pair().unwrap();
+        let mut lidar = Lidar::init_with_baud(port.name().unwrap(), 115200).unwrap();
+        assert_eq!(lidar.with_port(|port| port.baud_rate()).unwrap(), 115200);
+    }
+
+    /// Standard scan of `n` samples 1° apart, starting a revolution, as recorded after a scan request
+    fn standard_scan_recording(n: u16) -> Vec<u8> {
+        let mut bytes = vec![0xa5, 0x5a, 0x05, 0x00, 0x00, 0x40, 0x81];
+        for i in 0..n {
+            let angle = ((i << 6) << 1) | 1;
+            let distance = if i % 2 == 0 { 4000u16 } else { 0 };
+            bytes.extend([if i == 0 { 0xbd } else { 0xbe }, angle as u8, (angle >> 8) as u8, distance as u8, (distance >> 8) as u8]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn timed_out_revolution_is_completed_by_the_next_call() {
+        let lidar = Lidar::from_replay(Vec::new());
+        let sample = |start| Sample {
+            start,
+            intensity: 47,
+            angle_q6: 0,
+            distance: 1000,
+            timestamp: Instant::now(),
+        };
+        lidar.scan_buffer.lock().unwrap().extend([sample(true), sample(false)]);
+        assert!(matches!(lidar.get_full_scan_timeout(Duration::ZERO), Err(RxError::TimedOut)));
+
+        lidar.scan_buffer.lock().unwrap().extend([sample(false), sample(true)]);
+        assert_eq!(lidar.get_full_scan_timeout(Duration::ZERO).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn get_n_samples_returns_once_the_scan_ends() {
+        let mut lidar = Lidar::from_replay(standard_scan_recording(10));
+        lidar.spin_up_delay = Duration::ZERO;
+        lidar.start_scan().unwrap();
+
+        assert_eq!(lidar.get_n_samples(20).len(), 10);
+    }
+
+    #[test]
+    fn get_n_samples_timeout_returns_once_the_scan_ends() {
+        let mut lidar = Lidar::from_replay(standard_scan_recording(10));
+        lidar.spin_up_delay = Duration::ZERO;
+        lidar.start_scan().unwrap();
+
+        let started = Instant::now();
+        assert_eq!(lidar.get_n_samples_timeout(20, Duration::from_secs(10)).len(), 10);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn get_n_valid_samples_returns_once_the_scan_ends() {
+        let mut lidar = Lidar::from_replay(standard_scan_recording(10));
+        lidar.spin_up_delay = Duration::ZERO;
+        lidar.start_scan().unwrap();
+
+        assert_eq!(lidar.get_n_valid_samples(20).len(), 5);
+    }
+
+    #[test]
+    fn read_revolution_bytes_needs_a_raw_scan() {
+        let mut lidar = Lidar::from_replay(standard_scan_recording(10));
+        lidar.spin_up_delay = Duration::ZERO;
+        lidar.start_scan().unwrap();
+
+        assert!(lidar.read_revolution_bytes().is_empty());
+    }
+
+    #[test]
+    fn read_revolution_bytes_returns_one_revolution() {
+        let mut recording = standard_scan_recording(10);
+        recording.extend_from_within(7..);
+        let mut lidar = Lidar::from_replay(recording.clone());
+        lidar.spin_up_delay = Duration::ZERO;
+        lidar.start_scan_raw().unwrap();
+
+        assert_eq!(lidar.read_revolution_bytes(), recording[7..57]);
+    }
+
+    #[test]
+    fn failed_reader_does_not_panic_the_next_scan() {
+        let channel = MockChannel::new(standard_scan_recording(1));
+        channel.unplug();
+        let mut lidar = Lidar::from_channel(channel.clone());
+        lidar.spin_up_delay = Duration::ZERO;
+        lidar.start_scan().unwrap();
+        while lidar.is_scanning() {
+            sleep(Duration::from_millis(1));
+        }
+
+        channel.feed(&standard_scan_recording(1));
+        assert!(lidar.start_scan().is_ok());
+        lidar.stop_scan();
+    }
+
+    #[test]
+    fn health_cannot_be_polled_while_scanning() {
+        let channel = MockChannel::new(standard_scan_recording(1));
+        let mut lidar = Lidar::from_channel(channel.clone());
+        lidar.spin_up_delay = Duration::from_millis(200);
+        lidar.start_scan().unwrap();
+
+        assert!(matches!(lidar.poll_health(), Err(RxError::AlreadyScanning)));
+        // only the scan request was sent
+        assert_eq!(channel.writes(), vec![vec![0xa5, 0x20]]);
+        lidar.stop_scan();
+    }
+
+    #[test]
+    fn replayed_response_survives_input_flush() {
+        let mut lidar = Lidar::from_replay(device_info_response());
+        assert_eq!(lidar.try_get_info().unwrap().model, 0x18);
+    }
+
+    #[test]
+    fn stale_bytes_are_flushed_before_a_request() {
+        let channel = MockChannel::new(device_info_response());
+        channel.receive(&[0x3e, 0x01, 0x02, 0x03, 0x04]);
+        let mut lidar = Lidar::from_channel(channel);
+        assert_eq!(lidar.try_get_info().unwrap().model, 0x18);
+    }
+}
\ No newline at end of file
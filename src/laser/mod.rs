@@ -1,23 +1,41 @@
+mod builder;
+mod decoder;
 mod lidar;
-pub(crate) mod cmd;
-mod protocol;
-
-pub use lidar::Lidar;
-
-// LIDAR Scan Mode
-// pub struct LidarScanMode {
-//     /// Mode id
-//     id: u16,
-//
-//     /// Time cost for one measurement (in microseconds)
-//     us_per_sample: c_float,
-//
-//     /// Max distance in this scan mode (in meters)
-//     max_distance: c_float,
-//
-//     /// The answer command code for this scan mode
-//     ans_type: u8,
-//
-//     /// The name of scan mode (padding with 0 if less than 64 characters)
-//     scan_mode: [char; 64],
-// }
+pub mod cmd;
+pub mod protocol;
+
+use cmd::{SlLidarResponseDeviceHealthT, SlLidarResponseDeviceInfoT, SlLidarResponseSampleRateT};
+
+pub use builder::LidarBuilder;
+pub use decoder::ScanDecoder;
+pub use lidar::{Lidar, LidarState, OverflowPolicy, Samples, ScanSession, ScanStats, Scans};
+
+/// Description of a lidar scan mode
+#[derive(Debug, Clone)]
+pub struct ScanMode {
+    /// Mode id
+    pub id: u16,
+
+    /// The name of scan mode
+    pub name: String,
+
+    /// Time cost for one measurement (in microseconds)
+    pub us_per_sample: f32,
+
+    /// Max distance in this scan mode (in meters)
+    pub max_distance: f32,
+
+    /// The answer command code for this scan mode
+    pub answer_type: u8,
+}
+
+/// Results of [`Lidar::self_test`]
+#[derive(Debug)]
+pub struct Diagnostics {
+    pub info: SlLidarResponseDeviceInfoT,
+    pub health: SlLidarResponseDeviceHealthT,
+    pub sample_rate: SlLidarResponseSampleRateT,
+
+    /// Whether the lidar reported itself healthy
+    pub ok: bool,
+}
\ No newline at end of file
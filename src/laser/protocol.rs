@@ -1,3 +1,7 @@
+use crate::error::RxError;
+use crate::laser::Lidar;
+use std::time::Instant;
+
 #[derive(Debug)]
 pub struct ResponseDescriptor {
     pub len: u32,
@@ -11,16 +15,190 @@ pub struct Response {
     pub data: Vec<u8>,
 }
 
+impl Response {
+    /// Descriptor sent ahead of the response data
+    pub fn descriptor(&self) -> &ResponseDescriptor {
+        &self.descriptor
+    }
+
+    /// Response data, without the descriptor
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Sample {
-    pub(crate) start: bool,
-    pub(crate) intensity: u8,
-    pub(crate) angle: u16,
-    pub(crate) distance: u16,
+    pub start: bool,
+    /// 6 bit quality in standard scans, express and dense scans report a fixed 0x2f for any return
+    pub intensity: u8,
+    /// angle in degrees, Q6 fixed point
+    pub angle_q6: u16,
+    /// distance in millimeters, 0 if there was no return in range, see [`Sample::is_valid`]
+    pub distance: u16,
+    /// when the reader thread received the sample
+    pub timestamp: Instant,
 }
 
-pub struct DenseSample {
-    pub(crate) start: bool,
-    pub(crate) angle: u16,
-    pub(crate) cabin: [u16; 40],
+impl Sample {
+    /// Angle of the sample in degrees
+    pub fn angle_deg(&self) -> f32 {
+        self.angle_q6 as f32 / 64.0
+    }
+
+    /// Distance of the sample in millimeters
+    pub fn distance_mm(&self) -> f32 {
+        self.distance as f32
+    }
+
+    /// Intensity scaled to 0.0-1.0
+    pub fn intensity_normalized(&self) -> f32 {
+        (self.intensity & 0x3f) as f32 / 63.0
+    }
+
+    /// Whether the lidar measured a return for this sample
+    ///
+    /// Samples without a return are kept in scans rather than dropped, so the direction can be treated as free space.
+    pub fn is_valid(&self) -> bool {
+        self.distance > 0
+    }
+
+    /// Position of the sample relative to the lidar as (x, y) in millimeters
+    pub fn to_cartesian(&self) -> (f32, f32) {
+        let (sin, cos) = self.angle_deg().to_radians().sin_cos();
+        (self.distance_mm() * cos, self.distance_mm() * sin)
+    }
+
+    /// Position of the sample in the frame the lidar is mounted in, see [`Transform`]
+    ///
+    /// Mirroring is applied in the lidar's frame, before rotating and translating.
+    pub fn transformed(&self, t: &Transform) -> (f32, f32) {
+        let (x, y) = self.to_cartesian();
+        let y = if t.mirror { -y } else { y };
+        let (sin, cos) = t.angle_offset_deg.to_radians().sin_cos();
+        (x * cos - y * sin + t.x_offset_mm, x * sin + y * cos + t.y_offset_mm)
+    }
+}
+
+/// Mounting pose of the lidar relative to another frame, e.g. the robot origin
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Transform {
+    /// rotation of the lidar in degrees, counterclockwise
+    pub angle_offset_deg: f32,
+    /// position of the lidar in millimeters
+    pub x_offset_mm: f32,
+    pub y_offset_mm: f32,
+    /// whether the lidar is mounted upside down
+    pub mirror: bool,
+}
+
+/// Serializes the scaled angle and distance rather than the raw fixed point values
+#[cfg(feature = "serde")]
+impl serde::Serialize for Sample {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Sample", 4)?;
+        state.serialize_field("angle_deg", &self.angle_deg())?;
+        state.serialize_field("distance_mm", &self.distance_mm())?;
+        state.serialize_field("intensity", &self.intensity)?;
+        state.serialize_field("start", &self.start)?;
+        state.end()
+    }
+}
+
+/// Parsed express scan capsule, see [`decode_capsule`]
+#[derive(Debug, Clone)]
+pub struct ExpressCapsule {
+    /// set on the first capsule after the scan (re)started
+    pub start: bool,
+    /// angle of the first sample in degrees, Q6 fixed point
+    pub start_angle_q6: u16,
+    pub cabins: [ExpressCabin; 16],
+}
+
+/// Pair of samples in an express scan capsule
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpressCabin {
+    /// distances in millimeters
+    pub distances: [u16; 2],
+    /// amounts to subtract from the interpolated angles in degrees, Q3 fixed point
+    pub offsets_q3: [u8; 2],
+}
+
+/// Parses an express scan capsule, failing if its sync bits or checksum are wrong
+///
+/// Sample angles are interpolated between the start angles of consecutive capsules.
+pub fn decode_capsule(bytes: &[u8; 84]) -> Result<ExpressCapsule, RxError> {
+    if !validate_capsule(bytes) {
+        return Err(RxError::Corrupted(bytes.to_vec()));
+    }
+    Ok(parse_capsule(bytes))
+}
+
+/// Checks the sync nibbles and checksum of an express, dense or ultra capsule
+pub fn validate_capsule(bytes: &[u8]) -> bool {
+    let checksum = (bytes[1] << 4) | (bytes[0] & 0xf);
+    bytes[0] >> 4 == 0xa && bytes[1] >> 4 == 0x5 && Lidar::checksum(&bytes[2..]) == checksum
+}
+
+/// Parses an express scan capsule already checked with [`validate_capsule`]
+pub(crate) fn parse_capsule(bytes: &[u8; 84]) -> ExpressCapsule {
+    let mut cabins = [ExpressCabin::default(); 16];
+    for (cabin, raw) in cabins.iter_mut().zip(bytes[4..].chunks_exact(5)) {
+        let distance_angle = [
+            u16::from_le_bytes([raw[0], raw[1]]),
+            u16::from_le_bytes([raw[2], raw[3]]),
+        ];
+        *cabin = ExpressCabin {
+            distances: [distance_angle[0] >> 2, distance_angle[1] >> 2],
+            offsets_q3: [
+                (raw[4] & 0xf) | (((distance_angle[0] & 0b11) as u8) << 4),
+                (raw[4] >> 4) | (((distance_angle[1] & 0b11) as u8) << 4),
+            ],
+        };
+    }
+
+    ExpressCapsule {
+        start: bytes[3] & 0x80 != 0,
+        start_angle_q6: u16::from_le_bytes([bytes[2], bytes[3] & 0x7f]),
+        cabins,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Restarted capsule at 90° whose first cabin holds 1000 mm at offset 2.25° and 2000 mm at offset 0.625°
+    const CAPSULE_HEAD: [u8; 9] = [0xa5, 0x5b, 0x80, 0x96, 0xa1, 0x0f, 0x40, 0x1f, 0x52];
+
+    fn known_capsule() -> [u8; 84] {
+        let mut capsule = [0; 84];
+        capsule[..CAPSULE_HEAD.len()].copy_from_slice(&CAPSULE_HEAD);
+        capsule
+    }
+
+    #[test]
+    fn decodes_known_capsule() {
+        let capsule = decode_capsule(&known_capsule()).unwrap();
+        assert!(capsule.start);
+        assert_eq!(capsule.start_angle_q6, 90 << 6);
+        assert_eq!(capsule.cabins[0].distances, [1000, 2000]);
+        assert_eq!(capsule.cabins[0].offsets_q3, [0x12, 0x05]);
+        assert!(capsule.cabins[1..].iter().all(|cabin| cabin.distances == [0, 0]));
+    }
+
+    #[test]
+    fn rejects_capsule_with_bad_checksum() {
+        let mut bytes = known_capsule();
+        bytes[5] ^= 0x01;
+        assert!(matches!(decode_capsule(&bytes), Err(RxError::Corrupted(_))));
+
+        // sync nibbles swapped
+        let mut bytes = known_capsule();
+        bytes[0] = 0x55;
+        bytes[1] = 0xab;
+        assert!(!validate_capsule(&bytes));
+    }
 }
\ No newline at end of file
@@ -2,4 +2,10 @@
 pub mod examples;
 pub mod laser;
 mod util;
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod export;
+pub mod scan;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "ffi")]
+pub mod ffi;
\ No newline at end of file
@@ -6,9 +6,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     // initialize lidar
     let mut lidar = Lidar::init(String::from("COM3")).expect("Lidar should have initialized");
 
-    #[cfg(feature = "examples")] {
-        rangefinder::examples::print_modes(&mut lidar)?;
-    }
+    #[cfg(feature = "examples")]
+    rangefinder::examples::print_modes(&mut lidar)?;
+    #[cfg(not(feature = "examples"))]
+    println!("{:?}", lidar.try_get_info()?);
 
     Ok(())
 }
@@ -0,0 +1,206 @@
+use crate::laser::protocol::Sample;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Keeps valid samples with at least `min_intensity` and a distance within `min_distance..=max_distance` millimeters
+pub fn filter_scan(samples: &[Sample], min_intensity: u8, min_distance: u16, max_distance: u16) -> Vec<Sample> {
+    samples
+        .iter()
+        .filter(|sample| {
+            sample.is_valid()
+                && sample.intensity >= min_intensity
+                && (min_distance..=max_distance).contains(&sample.distance)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Bins samples by angle into `bins` equal sectors, keeping the closest valid distance in each
+pub fn resample_scan(samples: &[Sample], bins: usize) -> Vec<Option<u16>> {
+    let mut resampled = vec![None; bins];
+    if bins == 0 {
+        return resampled;
+    }
+
+    for sample in samples.iter().filter(|sample| sample.is_valid()) {
+        let bin = (sample.angle_deg() / 360.0 * bins as f32).round() as usize % bins;
+        let closest = resampled[bin].get_or_insert(sample.distance);
+        *closest = (*closest).min(sample.distance);
+    }
+    resampled
+}
+
+/// Resamples each revolution like [`resample_scan`] and returns the median distance of each bin across them
+///
+/// Bins without a return in any revolution stay `None`.
+pub fn merge_scans(scans: &[Vec<Sample>], bins: usize) -> Vec<Option<u16>> {
+    let mut distances: Vec<Vec<u16>> = vec![Vec::with_capacity(scans.len()); bins];
+    for scan in scans {
+        for (bin, distance) in resample_scan(scan, bins).into_iter().enumerate() {
+            distances[bin].extend(distance);
+        }
+    }
+
+    distances
+        .into_iter()
+        .map(|mut bin| {
+            if bin.is_empty() {
+                return None;
+            }
+            bin.sort_unstable();
+            let mid = bin.len() / 2;
+            // the mean of the middle two for an even count
+            Some(if bin.len() % 2 == 0 {
+                ((bin[mid - 1] as u32 + bin[mid] as u32) / 2) as u16
+            } else {
+                bin[mid]
+            })
+        })
+        .collect()
+}
+
+/// Sorts a revolution by ascending angle
+pub fn sort_scan_by_angle(samples: &mut [Sample]) {
+    samples.sort_by_key(|sample| sample.angle_q6);
+}
+
+/// Returns a copy of a revolution sorted by ascending angle
+pub fn sorted_by_angle(samples: &[Sample]) -> Vec<Sample> {
+    let mut sorted = samples.to_vec();
+    sort_scan_by_angle(&mut sorted);
+    sorted
+}
+
+/// Collapses samples of a revolution that share an angle, preserving order
+///
+/// Of each duplicate, the valid sample with the highest intensity is kept, the nearest on a tie.
+pub fn dedup_scan(samples: &[Sample]) -> Vec<Sample> {
+    let mut deduped: Vec<Sample> = Vec::with_capacity(samples.len());
+    let mut seen: HashMap<u16, usize> = HashMap::with_capacity(samples.len());
+
+    for sample in samples {
+        let Some(&index) = seen.get(&sample.angle_q6) else {
+            seen.insert(sample.angle_q6, deduped.len());
+            deduped.push(sample.clone());
+            continue;
+        };
+
+        let kept = &deduped[index];
+        let better = (sample.is_valid(), sample.intensity, Reverse(sample.distance))
+            > (kept.is_valid(), kept.intensity, Reverse(kept.distance));
+        if better {
+            deduped[index] = sample.clone();
+        }
+    }
+    deduped
+}
+
+/// Keeps every `keep_every`th sample of a revolution in order of angle
+///
+/// The first kept sample carries the revolution's `start` flag.
+pub fn downsample_scan(samples: &[Sample], keep_every: usize) -> Vec<Sample> {
+    let start = samples.iter().any(|sample| sample.start);
+    let mut downsampled: Vec<Sample> = sorted_by_angle(samples)
+        .into_iter()
+        .step_by(keep_every.max(1))
+        .collect();
+
+    for (i, sample) in downsampled.iter_mut().enumerate() {
+        sample.start = start && i == 0;
+    }
+    downsampled
+}
+
+/// Replaces the timestamps of a revolution with ones interpolated by angle
+///
+/// The first sample is taken to be captured at `rev_start`, and the rest spread evenly over `rev_duration`.
+pub fn assign_interpolated_timestamps(scan: &mut [Sample], rev_start: Instant, rev_duration: Duration) {
+    let Some(first) = scan.first() else {
+        return;
+    };
+    let start_q6 = first.angle_q6 as u32;
+
+    for sample in scan.iter_mut() {
+        let swept_q6 = (sample.angle_q6 as u32 + (360 << 6) - start_q6) % (360 << 6);
+        sample.timestamp = rev_start + rev_duration.mul_f64(swept_q6 as f64 / (360 << 6) as f64);
+    }
+}
+
+/// Counts valid samples per `bucket_mm` wide distance bucket, from 0 up to `max_mm`
+///
+/// Always returns `max_mm / bucket_mm + 1` buckets, so histograms of different scans line up.
+/// Samples farther than `max_mm` are ignored, see also [`Lidar::set_max_distance`](crate::laser::Lidar::set_max_distance).
+pub fn distance_histogram(samples: &[Sample], bucket_mm: u16, max_mm: u16) -> Vec<u32> {
+    let bucket_mm = bucket_mm.max(1) as usize;
+    let mut histogram = vec![0; max_mm as usize / bucket_mm + 1];
+
+    for sample in samples.iter().filter(|sample| sample.is_valid() && sample.distance <= max_mm) {
+        histogram[sample.distance as usize / bucket_mm] += 1;
+    }
+    histogram
+}
+
+/// Returns the closest valid sample within `half_width_deg` of `center_deg`, wrapping around 0/360
+pub fn nearest_in_window(samples: &[Sample], center_deg: f32, half_width_deg: f32) -> Option<Sample> {
+    samples
+        .iter()
+        .filter(|sample| {
+            // signed offset from the center in -180..180
+            let offset = (sample.angle_deg() - center_deg + 180.0).rem_euclid(360.0) - 180.0;
+            sample.is_valid() && offset.abs() <= half_width_deg
+        })
+        .min_by_key(|sample| sample.distance)
+        .cloned()
+}
+
+/// Returns the mean Cartesian position of the valid samples in millimeters, or `None` if there are none
+pub fn scan_centroid(samples: &[Sample]) -> Option<(f32, f32)> {
+    weighted_centroid(samples, |_| 1.0)
+}
+
+/// Like [`scan_centroid`], but weights each sample by its intensity
+pub fn scan_centroid_by_intensity(samples: &[Sample]) -> Option<(f32, f32)> {
+    weighted_centroid(samples, |sample| sample.intensity as f32)
+}
+
+/// Mean Cartesian position of the valid samples, each weighted by `weight`
+fn weighted_centroid(samples: &[Sample], weight: impl Fn(&Sample) -> f32) -> Option<(f32, f32)> {
+    let (mut x_sum, mut y_sum, mut total) = (0.0, 0.0, 0.0);
+    for sample in samples.iter().filter(|sample| sample.is_valid()) {
+        let (x, y) = sample.to_cartesian();
+        let w = weight(sample);
+        x_sum += x * w;
+        y_sum += y * w;
+        total += w;
+    }
+    (total > 0.0).then(|| (x_sum / total, y_sum / total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(distance: u16) -> Sample {
+        Sample {
+            start: false,
+            intensity: 47,
+            angle_q6: 0,
+            distance,
+            timestamp: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn histogram_has_fixed_bucket_count() {
+        assert_eq!(distance_histogram(&[], 100, 1000).len(), 11);
+
+        let samples = [sample(0), sample(50), sample(150), sample(1000), sample(4000)];
+        let histogram = distance_histogram(&samples, 100, 1000);
+        assert_eq!(histogram.len(), 11);
+        assert_eq!(histogram[0], 1);
+        assert_eq!(histogram[1], 1);
+        assert_eq!(histogram[10], 1);
+        assert_eq!(histogram.iter().sum::<u32>(), 3);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,197 @@
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::collections::VecDeque;
+use std::io;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct MockState {
+    /// bytes already received, discarded by clearing the input buffer
+    received: VecDeque<u8>,
+    /// bytes the device is still to send, served once `received` runs out
+    incoming: VecDeque<u8>,
+    writes: Vec<Vec<u8>>,
+    timeout: Duration,
+    /// reads fail with `BrokenPipe` rather than timing out once all bytes run out
+    unplugged: bool,
+}
+
+/// In-memory serial port serving reads from canned bytes and recording writes
+///
+/// Canned bytes count as future device output, so clearing the input buffer only
+/// discards bytes added with [`MockChannel::receive`]. Clones share the same state,
+/// like clones of a real port. Once all bytes run out, reads fail with [`io::ErrorKind::TimedOut`].
+#[derive(Clone)]
+pub struct MockChannel {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockChannel {
+    pub fn new(bytes: Vec<u8>) -> MockChannel {
+        MockChannel {
+            state: Arc::new(Mutex::new(MockState {
+                received: VecDeque::new(),
+                incoming: bytes.into(),
+                writes: Vec::new(),
+                timeout: Duration::from_millis(1000),
+                unplugged: false,
+            })),
+        }
+    }
+
+    /// Appends bytes the device is still to send
+    pub fn feed(&self, bytes: &[u8]) {
+        self.state.lock().unwrap().incoming.extend(bytes);
+    }
+
+    /// Appends bytes that were already received but not read, such as leftovers of a stopped scan
+    pub fn receive(&self, bytes: &[u8]) {
+        self.state.lock().unwrap().received.extend(bytes);
+    }
+
+    /// Fails reads with [`io::ErrorKind::BrokenPipe`] once all bytes run out, like an unplugged adapter
+    pub fn unplug(&self) {
+        self.state.lock().unwrap().unplugged = true;
+    }
+
+    /// All writes made so far, in order
+    pub fn writes(&self) -> Vec<Vec<u8>> {
+        self.state.lock().unwrap().writes.clone()
+    }
+}
+
+impl Read for MockChannel {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        let MockState { received, incoming, unplugged, .. } = &mut *state;
+        let rx = if received.is_empty() { incoming } else { received };
+        if rx.is_empty() && !buf.is_empty() {
+            let kind = if *unplugged { io::ErrorKind::BrokenPipe } else { io::ErrorKind::TimedOut };
+            return Err(kind.into());
+        }
+
+        let n = buf.len().min(rx.len());
+        for (dst, src) in buf.iter_mut().zip(rx.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for MockChannel {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.state.lock().unwrap().writes.push(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialPort for MockChannel {
+    fn name(&self) -> Option<String> {
+        Some(String::from("mock"))
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(256000)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::One)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.state.lock().unwrap().timeout
+    }
+
+    fn set_baud_rate(&mut self, _: u32) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, _: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, _: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_parity(&mut self, _: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, _: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.state.lock().unwrap().timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        let state = self.state.lock().unwrap();
+        Ok((state.received.len() + state.incoming.len()) as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if let ClearBuffer::Input | ClearBuffer::All = buffer_to_clear {
+            self.state.lock().unwrap().received.clear();
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
\ No newline at end of file